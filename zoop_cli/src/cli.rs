@@ -1,8 +1,15 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 use std::process::Command;
 use uuid::Uuid;
-use zoop_engine::networked_game;
+use zoop_engine::{
+    headless_game, headless_replay, networked_game, replay_game, sync_test_game, InputStream,
+    KeyBindings, Replay, ScriptedInput, SyncTestConfig, SyncTestInputs, SyncTestResult,
+    TrackDefinition,
+};
+use zoop_shared::car_tuning::CarTuning;
 use zoop_shared::player_id::PlayerId;
+use zoop_shared::race_result::{RaceResult, RACE_RESULT_PREFIX};
 use zoop_shared::room_config::GameRoomConfig;
 use zoop_shared::room_id::RoomId;
 
@@ -28,9 +35,92 @@ pub enum CLICommand {
         room_id: Uuid,
         #[arg(long)]
         room_config_json: String,
+        /// Race track JSON, the embedded track is used if omitted
+        #[arg(long)]
+        track_file: Option<PathBuf>,
+        /// Key bindings JSON, the settings file or WASD are used if omitted
+        #[arg(long)]
+        key_bindings_file: Option<PathBuf>,
+        /// Where to save the replay once the race is over or the game is closed
+        #[arg(long)]
+        replay_file: Option<PathBuf>,
+        /// Where to save desync reports, they aren't saved if omitted
+        #[arg(long)]
+        diagnostics_dir: Option<PathBuf>,
+    },
+    /// Runs a local race where every frame is resimulated to catch non-determinism
+    SyncTest {
+        #[arg(long, default_value_t = 2)]
+        players: usize,
+        /// How many frames back every frame is resimulated, must be less than 12
+        #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u8).range(1..12))]
+        check_distance: u8,
+        /// Stop after this many frames, runs until the window is closed if omitted
+        #[arg(long)]
+        frames: Option<i32>,
+        /// Seed for random inputs of all players
+        #[arg(long, conflicts_with = "input_script_file")]
+        seed: Option<u64>,
+        /// Scripted inputs JSON, a list of `{"frame", "handle", "steering", "throttle", "reverse", "brake", "handbrake"}`
+        #[arg(long)]
+        input_script_file: Option<PathBuf>,
+        /// Race track JSON, the embedded track is used if omitted
+        #[arg(long)]
+        track_file: Option<PathBuf>,
+        /// Car tuning JSON, the default handling is used if omitted
+        #[arg(long)]
+        tuning_file: Option<PathBuf>,
+        /// Where to save the replay once the test is over
+        #[arg(long)]
+        replay_file: Option<PathBuf>,
+    },
+    /// Simulates a race without a window and prints the final physics checksum
+    Headless {
+        /// Recorded inputs JSON, `{"player_count", "frames": [[{"input", "steering", "throttle", "reverse", "brake"}]]}`
+        #[arg(long, required_unless_present = "seed")]
+        input_file: Option<PathBuf>,
+        /// Seed for random inputs of all players, instead of recorded ones
+        #[arg(long, conflicts_with = "input_file", requires = "frames")]
+        seed: Option<u64>,
+        #[arg(long, default_value_t = 2)]
+        players: usize,
+        /// Frames to simulate, the whole input file if omitted
+        #[arg(long)]
+        frames: Option<i32>,
+        /// Race track JSON, the embedded track is used if omitted
+        #[arg(long)]
+        track_file: Option<PathBuf>,
+        /// Car tuning JSON, the default handling is used if omitted
+        #[arg(long)]
+        tuning_file: Option<PathBuf>,
+    },
+    /// Plays back a recorded race, Space pauses, Left/Right seek and Up/Down change speed
+    Replay {
+        #[arg(long)]
+        replay_file: PathBuf,
+        /// Resimulate without a window and print the final physics checksum
+        #[arg(long)]
+        headless: bool,
     },
 }
 
+fn load_track(track_file: Option<PathBuf>) -> Result<Option<TrackDefinition>, String> {
+    track_file
+        .map(|path| TrackDefinition::from_file(&path).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+fn load_tuning(tuning_file: Option<PathBuf>) -> Result<Option<CarTuning>, String> {
+    tuning_file
+        .map(|path| CarTuning::from_file(&path).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+fn load_input_script(path: PathBuf) -> Result<Vec<ScriptedInput>, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
 pub async fn run_command(command: CLICommand) {
     match command {
         CLICommand::ConnectGame {
@@ -40,9 +130,31 @@ pub async fn run_command(command: CLICommand) {
             user_ticket,
             room_id,
             room_config_json,
+            track_file,
+            key_bindings_file,
+            replay_file,
+            diagnostics_dir,
         } => {
             let room_config = serde_json::from_str(&room_config_json).unwrap();
-            networked_game(
+            let track = match load_track(track_file) {
+                Ok(track) => track,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return;
+                }
+            };
+            let key_bindings = match key_bindings_file {
+                Some(path) => KeyBindings::from_file(&path).map(Some),
+                None => KeyBindings::from_settings(),
+            };
+            let key_bindings = match key_bindings {
+                Ok(key_bindings) => key_bindings,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return;
+                }
+            };
+            let result = networked_game(
                 http_baseurl,
                 ws_baseurl,
                 PlayerId(user_id),
@@ -50,7 +162,126 @@ pub async fn run_command(command: CLICommand) {
                 RoomId(room_id),
                 room_config,
                 None,
+                track,
+                key_bindings,
+                replay_file,
+                diagnostics_dir,
             );
+            // Launchers tell a match which never started by the exit status
+            if let Err(error) = result {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        CLICommand::SyncTest {
+            players,
+            check_distance,
+            frames,
+            seed,
+            input_script_file,
+            track_file,
+            tuning_file,
+            replay_file,
+        } => {
+            let track = match load_track(track_file) {
+                Ok(track) => track,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return;
+                }
+            };
+            let tuning = match load_tuning(tuning_file) {
+                Ok(tuning) => tuning,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return;
+                }
+            };
+            let inputs = match (seed, input_script_file) {
+                (Some(seed), _) => SyncTestInputs::Random { seed },
+                (None, Some(path)) => match load_input_script(path) {
+                    Ok(script) => SyncTestInputs::Scripted(script),
+                    Err(error) => {
+                        eprintln!("Failed to load input script: {}", error);
+                        return;
+                    }
+                },
+                (None, None) => SyncTestInputs::Local,
+            };
+            let sync_test = SyncTestConfig {
+                player_count: players,
+                check_distance: usize::from(check_distance),
+                inputs,
+                frames,
+            };
+            let key_bindings = KeyBindings::from_settings().ok().flatten();
+
+            match sync_test_game(sync_test, track, tuning, key_bindings, replay_file) {
+                Some(result @ SyncTestResult::Mismatch { .. }) => {
+                    eprintln!("{}", result);
+                    std::process::exit(1);
+                }
+                Some(result) => println!("{}", result),
+                None => println!("Sync test stopped without a result"),
+            }
+        }
+        CLICommand::Headless {
+            input_file,
+            seed,
+            players,
+            frames,
+            track_file,
+            tuning_file,
+        } => {
+            let track = match load_track(track_file) {
+                Ok(track) => track,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return;
+                }
+            };
+            let tuning = match load_tuning(tuning_file) {
+                Ok(tuning) => tuning,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return;
+                }
+            };
+            let inputs = match (seed, input_file) {
+                (Some(seed), _) => InputStream::generate(
+                    &SyncTestInputs::Random { seed },
+                    players,
+                    frames.unwrap_or_default(),
+                ),
+                (None, Some(path)) => match InputStream::from_file(&path) {
+                    Ok(inputs) => inputs,
+                    Err(error) => {
+                        eprintln!("{}", error);
+                        return;
+                    }
+                },
+                (None, None) => unreachable!("Clap requires an input file or a seed"),
+            };
+
+            println!("{}", headless_game(inputs, frames, track, tuning));
+        }
+        CLICommand::Replay {
+            replay_file,
+            headless,
+        } => {
+            let replay = match Replay::from_file(&replay_file) {
+                Ok(replay) => replay,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return;
+                }
+            };
+
+            if headless {
+                println!("{}", headless_replay(replay, None));
+            } else {
+                replay_game(replay, None);
+            }
         }
     };
 }
@@ -63,7 +294,7 @@ pub fn exec_connect_game(
     user_ticket: String,
     room_id: RoomId,
     room_config: GameRoomConfig,
-) -> Result<(), String> {
+) -> Result<Option<RaceResult>, String> {
     let exe = std::env::current_exe().unwrap();
     let user_uuid = user_id.0.to_string();
     let room_uuid = room_id.0.to_string();
@@ -86,9 +317,20 @@ pub fn exec_connect_game(
 
     println!("{:?}, {:?}", exe, args);
 
-    Command::new(exe)
+    let output = Command::new(exe)
         .args(args)
         .output()
-        .map(|_| ())
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.lines().last().unwrap_or_default().to_string());
+    }
+
+    // The game prints its race result on stdout, if the race was finished
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix(RACE_RESULT_PREFIX))
+        .map(|json| serde_json::from_str(json).map_err(|e| e.to_string()))
+        .transpose()
 }
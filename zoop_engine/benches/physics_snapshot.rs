@@ -0,0 +1,159 @@
+//! Rolling back the whole `RapierContext` compared with `PhysicsSnapshot`s.
+//! Run with `cargo bench --bench physics_snapshot` in `zoop_engine/`.
+#![feature(test)]
+
+extern crate test;
+
+use bevy_rapier2d::plugin::RapierContext;
+use bevy_rapier2d::rapier::prelude::*;
+use test::Bencher;
+use zoop_engine::{PhysicsSnapshot, TrackDefinition};
+
+const TIRE_OFFSETS: [(f32, f32); 4] = [(-0.9, 1.3), (0.9, 1.3), (-0.9, -1.3), (0.9, -1.3)];
+
+/// The embedded track with cars on the starting grid, after one step
+fn populated_context(cars: usize) -> RapierContext {
+    let track = TrackDefinition::default();
+    let mut rapier = RapierContext::default();
+
+    for wall in track.walls.iter() {
+        let collider = ColliderBuilder::cuboid(wall.rect.half_width, wall.rect.half_height)
+            .translation(vector![wall.rect.x, wall.rect.y])
+            .rotation(wall.rect.angle.to_radians())
+            .build();
+        rapier.colliders.insert(collider);
+    }
+
+    for handle in 0..cars {
+        let (position, angle) = track.start.grid_slot(handle);
+        let car = rapier.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector![position.x, position.y])
+                .rotation(angle)
+                .build(),
+        );
+        rapier.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(1.0, 2.0).build(),
+            car,
+            &mut rapier.bodies,
+        );
+
+        for (x, y) in TIRE_OFFSETS {
+            let tire = rapier.bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(vector![position.x + x, position.y + y])
+                    .rotation(angle)
+                    .build(),
+            );
+            rapier.colliders.insert_with_parent(
+                ColliderBuilder::cuboid(0.2, 0.4).build(),
+                tire,
+                &mut rapier.bodies,
+            );
+            let joint = FixedJointBuilder::new().local_anchor1(point![x, y]);
+            rapier.impulse_joints.insert(car, tire, joint, true);
+        }
+    }
+
+    // Populates the broad and narrow phases like in a race
+    let mut pipeline = PhysicsPipeline::new();
+    pipeline.step(
+        &Vector::zeros(),
+        &rapier.integration_parameters,
+        &mut rapier.islands,
+        &mut rapier.broad_phase,
+        &mut rapier.narrow_phase,
+        &mut rapier.bodies,
+        &mut rapier.colliders,
+        &mut rapier.impulse_joints,
+        &mut rapier.multibody_joints,
+        &mut rapier.ccd_solver,
+        Some(&mut rapier.query_pipeline),
+        &(),
+        &(),
+    );
+
+    rapier
+}
+
+fn full_save(b: &mut Bencher, cars: usize) {
+    let rapier = populated_context(cars);
+    b.iter(|| bincode::serialize(&rapier).unwrap());
+}
+
+fn full_restore(b: &mut Bencher, cars: usize) {
+    let bytes = bincode::serialize(&populated_context(cars)).unwrap();
+    b.iter(|| bincode::deserialize::<RapierContext>(&bytes).unwrap());
+}
+
+fn snapshot_save(b: &mut Bencher, cars: usize) {
+    let rapier = populated_context(cars);
+    let mut bytes = vec![];
+    b.iter(|| PhysicsSnapshot::save_into(&rapier, &mut bytes));
+}
+
+fn snapshot_restore(b: &mut Bencher, cars: usize) {
+    let mut rapier = populated_context(cars);
+    let bytes = PhysicsSnapshot::save(&rapier);
+    b.iter(|| assert!(PhysicsSnapshot::restore(&mut rapier, &bytes)));
+}
+
+#[bench]
+fn full_save_2_cars(b: &mut Bencher) {
+    full_save(b, 2);
+}
+
+#[bench]
+fn full_save_4_cars(b: &mut Bencher) {
+    full_save(b, 4);
+}
+
+#[bench]
+fn full_save_8_cars(b: &mut Bencher) {
+    full_save(b, 8);
+}
+
+#[bench]
+fn full_restore_2_cars(b: &mut Bencher) {
+    full_restore(b, 2);
+}
+
+#[bench]
+fn full_restore_4_cars(b: &mut Bencher) {
+    full_restore(b, 4);
+}
+
+#[bench]
+fn full_restore_8_cars(b: &mut Bencher) {
+    full_restore(b, 8);
+}
+
+#[bench]
+fn snapshot_save_2_cars(b: &mut Bencher) {
+    snapshot_save(b, 2);
+}
+
+#[bench]
+fn snapshot_save_4_cars(b: &mut Bencher) {
+    snapshot_save(b, 4);
+}
+
+#[bench]
+fn snapshot_save_8_cars(b: &mut Bencher) {
+    snapshot_save(b, 8);
+}
+
+#[bench]
+fn snapshot_restore_2_cars(b: &mut Bencher) {
+    snapshot_restore(b, 2);
+}
+
+#[bench]
+fn snapshot_restore_4_cars(b: &mut Bencher) {
+    snapshot_restore(b, 4);
+}
+
+#[bench]
+fn snapshot_restore_8_cars(b: &mut Bencher) {
+    snapshot_restore(b, 8);
+}
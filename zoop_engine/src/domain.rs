@@ -1,16 +1,34 @@
 pub mod car;
 pub mod car_body;
+pub mod car_class;
 pub mod checksum;
 pub mod colors;
 pub mod controls;
+pub mod damage;
 pub mod desync;
+pub mod desync_report;
+pub mod frame_inputs;
 pub mod frames;
 pub mod game_config;
+pub mod game_font;
+pub mod game_mode;
 pub mod game_set;
 pub mod game_state;
 pub mod ggrs_config;
+pub mod handshake;
+pub mod input_stream;
+pub mod key_bindings;
+pub mod physics_snapshot;
 pub mod player;
+pub mod race;
 pub mod rapier_rollback_state;
+pub mod rapier_serde;
+pub mod replay;
+pub mod resync;
 pub mod room_config;
 pub mod spawn;
+pub mod surface;
+pub mod sync_test;
 pub mod tire;
+pub mod track;
+pub mod track_body;
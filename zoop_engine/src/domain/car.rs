@@ -1,6 +1,7 @@
 use crate::domain::car_body::*;
 use crate::domain::game_state::*;
 use crate::domain::player::Player;
+use crate::domain::spawn::{SpawnError, SpawnPool};
 use crate::domain::tire::Tire;
 use crate::logic::math::*;
 use bevy::prelude::*;
@@ -9,7 +10,7 @@ use bevy_rapier2d::prelude::*;
 
 pub fn spawn_car(
     commands: &mut Commands,
-    spawn_pool: &mut Vec<Entity>,
+    spawn_pool: &mut SpawnPool,
     rip: &mut RollbackIdProvider,
     player: Player,
     car_title: String,
@@ -18,14 +19,17 @@ pub fn spawn_car(
     car_color: Color,
     tire_color: Color,
     tire_damping: Damping,
+    contact_force_threshold: f32,
     car_physics: GameCar,
-) {
-    let mut car = commands.entity(spawn_pool.pop().unwrap());
+) -> Result<(), SpawnError> {
+    let mut car = commands.entity(spawn_pool.pop()?);
     car.insert(CarBody::build(
         car_title.clone(),
         car_half_size,
+        car_physics.spec.density,
         player.clone(),
         car_color,
+        contact_force_threshold,
         car_physics.physics.clone(),
     ));
     car.insert(Rollback::new(rip.next_id()));
@@ -41,14 +45,15 @@ pub fn spawn_car(
         tire_half_size,
         tire_color,
         tire_damping,
+        contact_force_threshold,
         car_title,
         car_physics,
-    );
+    )
 }
 
 fn spawn_tire(
     commands: &mut Commands,
-    spawn_pool: &mut Vec<Entity>,
+    spawn_pool: &mut SpawnPool,
     rip: &mut RollbackIdProvider,
     player: Player,
     car: Entity,
@@ -59,9 +64,10 @@ fn spawn_tire(
     car_title: String,
     color: Color,
     damping: Damping,
+    contact_force_threshold: f32,
     physics: GameTire,
-) {
-    let mut tire = commands.entity(spawn_pool.pop().unwrap());
+) -> Result<(), SpawnError> {
+    let mut tire = commands.entity(spawn_pool.pop()?);
     tire.insert(Tire::build(
         player,
         is_front,
@@ -70,15 +76,17 @@ fn spawn_tire(
         car_title,
         color,
         damping,
+        contact_force_threshold,
         physics,
     ));
     tire.insert(Rollback::new(rip.next_id()));
     tire.insert(ImpulseJoint::new(car, car_anchor));
+    Ok(())
 }
 
 pub fn spawn_tires(
     commands: &mut Commands,
-    spawn_pool: &mut Vec<Entity>,
+    spawn_pool: &mut SpawnPool,
     rip: &mut RollbackIdProvider,
     player: Player,
     car: Entity,
@@ -86,9 +94,10 @@ pub fn spawn_tires(
     tire_half_size: Vec2,
     color: Color,
     damping: Damping,
+    contact_force_threshold: f32,
     car_title: String,
     car_physics: GameCar,
-) {
+) -> Result<(), SpawnError> {
     spawn_tire(
         commands,
         spawn_pool,
@@ -102,8 +111,9 @@ pub fn spawn_tires(
         car_title.clone(),
         color,
         damping,
+        contact_force_threshold,
         car_physics.tire_top_right,
-    );
+    )?;
     spawn_tire(
         commands,
         spawn_pool,
@@ -117,8 +127,9 @@ pub fn spawn_tires(
         car_title.clone(),
         color,
         damping,
+        contact_force_threshold,
         car_physics.tire_top_left,
-    );
+    )?;
     spawn_tire(
         commands,
         spawn_pool,
@@ -132,8 +143,9 @@ pub fn spawn_tires(
         car_title.clone(),
         color,
         damping,
+        contact_force_threshold,
         car_physics.tire_bottom_right,
-    );
+    )?;
     spawn_tire(
         commands,
         spawn_pool,
@@ -147,8 +159,9 @@ pub fn spawn_tires(
         car_title.clone(),
         color,
         damping,
+        contact_force_threshold,
         car_physics.tire_bottom_left,
-    );
+    )
 }
 
 pub fn tire_anchor(
@@ -170,14 +183,19 @@ pub fn tire_anchor(
 
 pub fn tire_position(
     car_position: Vec3,
+    car_angle: f32,
     car_half_size: Vec2,
     tire_half_size: Vec2,
     is_front: bool,
     is_right: bool,
 ) -> Vec3 {
+    let offset = Vec2::from_angle(car_angle).rotate(Vec2 {
+        x: signed(is_right, car_half_size.x + tire_half_size.x * 3.0),
+        y: signed(is_front, car_half_size.y - tire_half_size.y),
+    });
     Vec3 {
-        x: car_position.x + signed(is_right, car_half_size.x + tire_half_size.x * 3.0),
-        y: car_position.y + signed(is_front, car_half_size.y - tire_half_size.y),
+        x: car_position.x + offset.x,
+        y: car_position.y + offset.y,
         z: 0.0,
     }
 }
@@ -21,11 +21,13 @@ pub struct CarBody {
     impulse: ExternalImpulse,
     velocity: Velocity,
     collider: Collider,
+    collider_mass: ColliderMassProperties,
     collider_scale: ColliderScale,
     locked_axes: LockedAxes,
     restitution: Restitution,
     friction: Friction,
     active_events: ActiveEvents,
+    contact_force_threshold: ContactForceEventThreshold,
     ccd: Ccd,
     collision_groups: CollisionGroups,
     sprite: SpriteBundle,
@@ -36,8 +38,10 @@ impl CarBody {
     pub fn build(
         car_title: String,
         half_size: Vec2,
+        density: f32,
         player: Player,
         color: Color,
+        contact_force_threshold: f32,
         physics: EntityPhysics,
     ) -> CarBody {
         CarBody {
@@ -50,11 +54,14 @@ impl CarBody {
             force: physics.force,
             impulse: physics.impulse,
             collider: Collider::cuboid(half_size.x, half_size.y),
+            collider_mass: ColliderMassProperties::Density(density),
             collider_scale: ColliderScale::Absolute(Vec2::new(1., 1.)),
             locked_axes: LockedAxes::default(),
             restitution: Restitution::default(),
             friction: Friction::default(),
-            active_events: ActiveEvents::empty(),
+            // Collisions damage the car
+            active_events: ActiveEvents::CONTACT_FORCE_EVENTS,
+            contact_force_threshold: ContactForceEventThreshold(contact_force_threshold),
             ccd: Ccd::disabled(),
             collision_groups: CollisionGroups::default(),
             velocity: physics.velocity,
@@ -0,0 +1,149 @@
+use crate::domain::colors::*;
+use crate::domain::game_config::GameConfig;
+use crate::domain::tire::TireMeta;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use zoop_shared::car_class::CarClass;
+
+/// Which wheels are powered
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Reflect, FromReflect, PartialEq)]
+pub enum Drivetrain {
+    #[default]
+    FrontWheel,
+    /// Tends to oversteer under throttle
+    RearWheel,
+    /// Share of the torque which goes to the front axle, from 0.0 to 1.0
+    AllWheel { front_share: f32 },
+}
+
+impl Drivetrain {
+    pub fn of_front_share(front_share: f32) -> Drivetrain {
+        if front_share >= 1.0 {
+            Drivetrain::FrontWheel
+        } else if front_share <= 0.0 {
+            Drivetrain::RearWheel
+        } else {
+            Drivetrain::AllWheel { front_share }
+        }
+    }
+
+    pub fn front_share(&self) -> f32 {
+        match self {
+            Drivetrain::FrontWheel => 1.0,
+            Drivetrain::RearWheel => 0.0,
+            Drivetrain::AllWheel { front_share } => front_share.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// How a class differs from the base car described by `GameConfig` and the car tuning
+struct CarClassTraits {
+    body_scale: Vec2,
+    tire_scale: Vec2,
+    density: f32,
+    acceleration_scale: f32,
+    reversing_scale: f32,
+    breaking_scale: f32,
+    steering_scale: f32,
+    drivetrain: Drivetrain,
+    rear_steering_ratio: f32,
+}
+
+fn class_traits(class: CarClass) -> CarClassTraits {
+    match class {
+        CarClass::Hatchback => CarClassTraits {
+            body_scale: Vec2::new(1.0, 1.0),
+            tire_scale: Vec2::new(1.0, 1.0),
+            density: 1.0,
+            acceleration_scale: 1.0,
+            reversing_scale: 1.0,
+            breaking_scale: 1.0,
+            steering_scale: 1.0,
+            drivetrain: Drivetrain::FrontWheel,
+            rear_steering_ratio: 0.0,
+        },
+        // Heavy and slow to turn, but hard to push around
+        CarClass::Truck => CarClassTraits {
+            body_scale: Vec2::new(1.3, 1.4),
+            tire_scale: Vec2::new(1.5, 1.25),
+            density: 1.5,
+            acceleration_scale: 1.6,
+            reversing_scale: 1.4,
+            breaking_scale: 2.0,
+            steering_scale: 0.7,
+            drivetrain: Drivetrain::AllWheel { front_share: 0.4 },
+            // Rear wheels turn against the front ones, so that the long body still gets around corners
+            rear_steering_ratio: 0.3,
+        },
+        // Light and twitchy
+        CarClass::Kart => CarClassTraits {
+            body_scale: Vec2::new(0.7, 0.6),
+            tire_scale: Vec2::new(0.75, 0.75),
+            density: 0.8,
+            acceleration_scale: 0.6,
+            reversing_scale: 0.5,
+            breaking_scale: 0.5,
+            steering_scale: 1.2,
+            drivetrain: Drivetrain::RearWheel,
+            rear_steering_ratio: 0.0,
+        },
+    }
+}
+
+pub fn car_color(class: CarClass) -> Color {
+    match class {
+        CarClass::Hatchback => ZOOP_RED,
+        CarClass::Truck => ZOOP_DARK_BLUE,
+        CarClass::Kart => ZOOP_ORANGE,
+    }
+}
+
+/// Dimensions and handling of one car in native Bevy units, rolled back as part of the game state
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Reflect, FromReflect, PartialEq)]
+pub struct CarSpec {
+    pub half_size: Vec2,
+    pub tire_half_size: Vec2,
+    pub density: f32,
+    pub acceleration_force: f32,
+    pub reversing_force: f32,
+    pub breaking_force: f32,
+    pub tire_max_angle: f32,
+    pub tire_rotation_per_tick: f32,
+    pub drivetrain: Drivetrain,
+    /// Rear wheel angle relative to the front wheel angle, positive values turn against the front
+    pub rear_steering_ratio: f32,
+}
+
+impl CarSpec {
+    pub fn of(class: CarClass, config: &GameConfig) -> CarSpec {
+        let traits = class_traits(class);
+        CarSpec {
+            half_size: config.car_half_size() * traits.body_scale,
+            tire_half_size: config.tire_half_size() * traits.tire_scale,
+            density: traits.density,
+            acceleration_force: config.tire_acceleration_force * traits.acceleration_scale,
+            reversing_force: config.tire_reversing_force * traits.reversing_scale,
+            breaking_force: config.tire_breaking_force * traits.breaking_scale,
+            tire_max_angle: config.tire_max_angle * traits.steering_scale,
+            tire_rotation_per_tick: config.tire_rotation_per_tick * traits.steering_scale,
+            drivetrain: config
+                .drive_front_share
+                .map(Drivetrain::of_front_share)
+                .unwrap_or(traits.drivetrain),
+            rear_steering_ratio: config
+                .rear_steering_ratio
+                .unwrap_or(traits.rear_steering_ratio),
+        }
+    }
+
+    /// Share of the axle's torque a tire gets, both tires of the powered axle of
+    /// a two-wheel drive car get the full force, like before drivetrains existed
+    pub fn drive_share(&self, tire_meta: &TireMeta) -> f32 {
+        let front_share = self.drivetrain.front_share();
+        if tire_meta.is_front {
+            front_share
+        } else {
+            1.0 - front_share
+        }
+    }
+}
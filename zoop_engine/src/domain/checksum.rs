@@ -1,12 +1,46 @@
-/// Computes the fletcher16 checksum, copied from wikipedia: <https://en.wikipedia.org/wiki/Fletcher%27s_checksum>
-pub fn fletcher16(data: &[u8]) -> u16 {
-    let mut sum1: u16 = 0;
-    let mut sum2: u16 = 0;
-
-    for byte in data {
-        sum1 = (sum1 + *byte as u16) % 255;
-        sum2 = (sum2 + sum1) % 255;
+use crate::domain::game_state::GameState;
+use crate::domain::physics_snapshot::SnapshotLayout;
+use serde::{Deserialize, Serialize};
+pub use zoop_shared::checksum::fnv1a64;
+
+fn checksum_of<T: Serialize>(value: &T) -> u64 {
+    bincode::serialize(value)
+        .map(|bytes| fnv1a64(&bytes))
+        .unwrap_or_default()
+}
+
+/// Checksums of the parts of the state, to tell which one diverged in a desync
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct StateChecksums {
+    pub bodies: u64,
+    pub colliders: u64,
+    pub joints: u64,
+    pub game_state: u64,
+}
+
+impl StateChecksums {
+    /// Hashes the parts of a saved `PhysicsSnapshot` instead of serializing them again,
+    /// only the game state, which is small and not serialized otherwise, gets serialized
+    pub fn of(snapshot: &[u8], layout: &SnapshotLayout, game_state: &GameState) -> StateChecksums {
+        StateChecksums {
+            bodies: fnv1a64(&snapshot[layout.bodies.clone()]),
+            colliders: fnv1a64(&snapshot[layout.colliders.clone()]),
+            joints: fnv1a64(&snapshot[layout.joints.clone()]),
+            game_state: checksum_of(game_state),
+        }
     }
 
-    (sum2 << 8) | sum1
+    /// Names of the parts which differ
+    pub fn diverged(&self, other: &StateChecksums) -> Vec<&'static str> {
+        [
+            ("bodies", self.bodies == other.bodies),
+            ("colliders", self.colliders == other.colliders),
+            ("joints", self.joints == other.joints),
+            ("game_state", self.game_state == other.game_state),
+        ]
+        .into_iter()
+        .filter(|(_, equal)| !equal)
+        .map(|(name, _)| name)
+        .collect()
+    }
 }
@@ -7,3 +7,7 @@ pub const ZOOP_YELLOW: Color = Color::rgb(1.000, 0.953, 0.690); // #fff3b0
 pub const ZOOP_ORANGE: Color = Color::rgb(0.878, 0.623, 0.243); // #e09f3e
 pub const ZOOP_RED: Color = Color::rgb(0.619, 0.165, 0.169); // #9e2a2b
 pub const ZOOP_DARK_RED: Color = Color::rgb(0.323, 0.043, 0.055); // #540b0e
+pub const ZOOP_GREY: Color = Color::rgb(0.553, 0.541, 0.502); // #8d8a80
+pub const ZOOP_GREEN: Color = Color::rgb(0.478, 0.604, 0.357); // #7a9a5b
+pub const ZOOP_LIGHT_BLUE: Color = Color::rgb(0.741, 0.878, 0.902); // #bde0e6
+pub const ZOOP_BROWN: Color = Color::rgb(0.498, 0.333, 0.224); // #7f5539
@@ -1,3 +1,4 @@
+use crate::domain::key_bindings::KeyBindings;
 use bevy::prelude::*;
 use bytemuck::{Pod, Zeroable};
 use ggrs::*;
@@ -7,34 +8,75 @@ const INPUT_REVERSE: u16 = 1 << 1;
 const INPUT_BREAK: u16 = 1 << 2;
 const INPUT_STEER_RIGHT: u16 = 1 << 3;
 const INPUT_STEER_LEFT: u16 = 1 << 4;
+const INPUT_REMATCH: u16 = 1 << 5;
+const INPUT_HANDBRAKE: u16 = 1 << 6;
+
+const ANALOG_MAX: u8 = u8::MAX;
+const STEERING_MAX: i8 = i8::MAX;
+
+/// Quantize an analog axis so that every peer simulates with exactly the same value
+fn quantize_axis(value: f32) -> i8 {
+    (value.clamp(-1.0, 1.0) * STEERING_MAX as f32).round() as i8
+}
+
+fn quantize_trigger(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * ANALOG_MAX as f32).round() as u8
+}
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Pod, Zeroable)]
 pub struct Controls {
+    pub last_confirmed_hash: u64,
+    pub last_confirmed_frame: Frame,
+
     pub input: u16,
+    /// Analog steering from full left -127 to full right 127
+    pub steering: i8,
+    /// Analog pedals from 0 to 255
+    pub throttle: u8,
+    pub reverse: u8,
+    pub brake: u8,
 
-    pub last_confirmed_hash: u16,
-    pub last_confirmed_frame: Frame,
+    /// `Pod` can't have implicit padding, GGRS compresses these zeroes away
+    pub padding: [u8; 6],
 }
 impl Controls {
     pub fn accelerating(&self) -> bool {
-        (self.input & INPUT_ACCELERATE) != 0
+        (self.input & INPUT_ACCELERATE) != 0 || self.throttle > 0
     }
     pub fn reversing(&self) -> bool {
-        (self.input & INPUT_REVERSE) != 0
+        (self.input & INPUT_REVERSE) != 0 || self.reverse > 0
     }
     pub fn breaking(&self) -> bool {
-        (self.input & INPUT_BREAK) != 0
+        (self.input & INPUT_BREAK) != 0 || self.brake > 0
     }
-    pub fn steering_right(&self) -> bool {
-        (self.input & INPUT_STEER_RIGHT) != 0
+    /// Cuts the grip of the rear tires
+    pub fn handbraking(&self) -> bool {
+        (self.input & INPUT_HANDBRAKE) != 0
     }
-    pub fn steering_left(&self) -> bool {
-        (self.input & INPUT_STEER_LEFT) != 0
+    pub fn set_handbrake(&mut self, handbrake: bool) {
+        if handbrake {
+            self.input |= INPUT_HANDBRAKE;
+        } else {
+            self.input &= !INPUT_HANDBRAKE;
+        }
     }
-
-    pub fn steering_any(&self) -> bool {
-        self.steering_right() || self.steering_left()
+    /// Wants to race the next round once the race is over
+    pub fn rematch(&self) -> bool {
+        (self.input & INPUT_REMATCH) != 0
+    }
+    /// Steering from -1.0 (full left) to 1.0 (full right)
+    pub fn steering_amount(&self) -> f32 {
+        self.steering as f32 / STEERING_MAX as f32
+    }
+    pub fn throttle_amount(&self) -> f32 {
+        self.throttle as f32 / ANALOG_MAX as f32
+    }
+    pub fn reverse_amount(&self) -> f32 {
+        self.reverse as f32 / ANALOG_MAX as f32
+    }
+    pub fn brake_amount(&self) -> f32 {
+        self.brake as f32 / ANALOG_MAX as f32
     }
 
     pub fn from_keys(
@@ -44,56 +86,129 @@ impl Controls {
         breaker: KeyCode,
         steer_right: KeyCode,
         steer_left: KeyCode,
-        last_confirmed_hash: u16,
+        handbrake: KeyCode,
+        rematch: KeyCode,
+        last_confirmed_hash: u64,
         last_confirmed_frame: Frame,
     ) -> Controls {
-        let mut serialized: u16 = 0;
+        let mut controls = Controls::empty(last_confirmed_hash, last_confirmed_frame);
 
+        // Digital keys are full analog values
         if input.pressed(accelerator) {
-            serialized |= INPUT_ACCELERATE
+            controls.input |= INPUT_ACCELERATE;
+            controls.throttle = ANALOG_MAX;
         }
         if input.pressed(reverser) {
-            serialized |= INPUT_REVERSE
+            controls.input |= INPUT_REVERSE;
+            controls.reverse = ANALOG_MAX;
         }
         if input.pressed(breaker) {
-            serialized |= INPUT_BREAK
+            controls.input |= INPUT_BREAK;
+            controls.brake = ANALOG_MAX;
         }
         if input.pressed(steer_right) {
-            serialized |= INPUT_STEER_RIGHT
+            controls.input |= INPUT_STEER_RIGHT;
+            controls.steering = STEERING_MAX;
         }
         if input.pressed(steer_left) {
-            serialized |= INPUT_STEER_LEFT
+            controls.input |= INPUT_STEER_LEFT;
+            controls.steering = -STEERING_MAX;
         }
-
-        Controls {
-            input: serialized,
-            last_confirmed_hash,
-            last_confirmed_frame,
+        if input.pressed(steer_right) && input.pressed(steer_left) {
+            controls.steering = 0;
+        }
+        if input.pressed(handbrake) {
+            controls.input |= INPUT_HANDBRAKE;
         }
+        if input.pressed(rematch) {
+            controls.input |= INPUT_REMATCH;
+        }
+
+        controls
     }
 
-    pub fn empty(last_confirmed_hash: u16, last_confirmed_frame: Frame) -> Controls {
+    pub fn empty(last_confirmed_hash: u64, last_confirmed_frame: Frame) -> Controls {
         Controls {
             input: 0,
+            steering: 0,
+            throttle: 0,
+            reverse: 0,
+            brake: 0,
             last_confirmed_hash,
             last_confirmed_frame,
+            padding: [0; 6],
         }
     }
 
-    pub fn from_wasd(
+    pub fn from_bindings(
         input: &Input<KeyCode>,
-        last_confirmed_hash: u16,
+        bindings: &KeyBindings,
+        last_confirmed_hash: u64,
         last_confirmed_frame: Frame,
     ) -> Controls {
         Controls::from_keys(
             input,
-            KeyCode::W,
-            KeyCode::S,
-            KeyCode::C,
-            KeyCode::D,
-            KeyCode::A,
+            bindings.accelerate,
+            bindings.reverse,
+            bindings.brake,
+            bindings.steer_right,
+            bindings.steer_left,
+            bindings.handbrake,
+            bindings.rematch,
             last_confirmed_hash,
             last_confirmed_frame,
         )
     }
+
+    /// Left stick steers, right trigger accelerates, left trigger reverses, east button brakes,
+    /// south button pulls the handbrake, start asks for a rematch
+    pub fn from_gamepad(
+        gamepad: Gamepad,
+        axes: &Axis<GamepadAxis>,
+        button_axes: &Axis<GamepadButton>,
+        last_confirmed_hash: u64,
+        last_confirmed_frame: Frame,
+    ) -> Controls {
+        let axis = |axis_type| {
+            axes.get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.0)
+        };
+        let button = |button_type| {
+            button_axes
+                .get(GamepadButton::new(gamepad, button_type))
+                .unwrap_or(0.0)
+        };
+
+        let rematch = button(GamepadButtonType::Start) > 0.5;
+        let handbrake = button(GamepadButtonType::South) > 0.5;
+
+        let mut controls = Controls {
+            input: if rematch { INPUT_REMATCH } else { 0 },
+            steering: quantize_axis(axis(GamepadAxisType::LeftStickX)),
+            throttle: quantize_trigger(button(GamepadButtonType::RightTrigger2)),
+            reverse: quantize_trigger(button(GamepadButtonType::LeftTrigger2)),
+            brake: quantize_trigger(button(GamepadButtonType::East)),
+            last_confirmed_hash,
+            last_confirmed_frame,
+            padding: [0; 6],
+        };
+        controls.set_handbrake(handbrake);
+        controls
+    }
+
+    /// Combines two input devices, the stronger input wins
+    pub fn merge(self, other: Controls) -> Controls {
+        Controls {
+            input: self.input | other.input,
+            steering: if other.steering.unsigned_abs() > self.steering.unsigned_abs() {
+                other.steering
+            } else {
+                self.steering
+            },
+            throttle: self.throttle.max(other.throttle),
+            reverse: self.reverse.max(other.reverse),
+            brake: self.brake.max(other.brake),
+            ..self
+        }
+    }
 }
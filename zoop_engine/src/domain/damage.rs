@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use ggrs::Frame;
+use serde::{Deserialize, Serialize};
+
+/// Per-car collision damage, rolled back as part of the game state
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Reflect, FromReflect, PartialEq)]
+pub struct CarDamage {
+    /// From 0.0 for an intact car to 1.0 for a wreck
+    pub amount: f32,
+    /// Collisions with other cars, a contact lasting several frames counts once
+    pub hits: u16,
+    /// Last frame the car was pushed by a wall or another car hard enough to get damaged
+    pub last_contact_frame: Frame,
+    /// Last frame the car touched another car, to tell new hits from ongoing contacts
+    pub last_hit_frame: Frame,
+}
+
+impl CarDamage {
+    pub fn take(&mut self, impulse: f32, damage_per_impulse: f32, frame: Frame) {
+        self.amount = (self.amount + impulse * damage_per_impulse).min(1.0);
+        self.last_contact_frame = frame;
+    }
+
+    pub fn record_hit(&mut self, frame: Frame) {
+        if self.hits == 0 || frame > self.last_hit_frame + 1 {
+            self.hits += 1;
+        }
+        self.last_hit_frame = frame;
+    }
+
+    /// Share of the drive force the engine still delivers
+    pub fn power_share(&self, max_power_loss: f32) -> f32 {
+        1.0 - self.amount * max_power_loss
+    }
+
+    /// Angle the front wheels are bent to the left by, in radians
+    pub fn steering_bend(&self, max_steering_bend: f32) -> f32 {
+        self.amount * max_steering_bend
+    }
+}
@@ -1,10 +1,17 @@
+use crate::domain::checksum::StateChecksums;
 use crate::domain::frames::*;
-use crate::domain::game_config::DESYNC_MAX_FRAMES;
+use crate::domain::game_config::{GameConfig, DESYNC_MAX_FRAMES};
+#[cfg(feature = "ggrs_desync_detection")]
 use crate::domain::ggrs_config::GGRSConfig;
 use bevy::prelude::*;
-use bevy_ggrs::{Rollback, Session};
+use bevy_ggrs::Rollback;
+#[cfg(feature = "ggrs_desync_detection")]
+use bevy_ggrs::Session;
 use bevy_rapier2d::prelude::*;
 use ggrs::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
 
 /// Metadata we need to store about frames we've rendered locally
 #[derive(Default, Hash, Resource, PartialEq, Eq, Debug)]
@@ -13,7 +20,10 @@ pub struct FrameHash {
     pub frame: Frame,
 
     /// The checksum of the Rapier physics state for the frame.  I use this term interchangably with `hash`, sorry.
-    pub rapier_checksum: u16,
+    pub rapier_checksum: u64,
+
+    /// Checksums of the parts of the state, only known locally
+    pub state_checksums: StateChecksums,
 
     /// Has been confirmed by GGRS
     pub confirmed: bool,
@@ -32,7 +42,7 @@ pub struct RxFrameHash {
     pub frame: Frame,
 
     /// The checksum of the Rapier physics state for the frame.  I use this term interchangably with `hash`, sorry.
-    pub rapier_checksum: u16,
+    pub rapier_checksum: u64,
 
     /// Has been validated by us against other player
     pub validated: bool,
@@ -42,75 +52,157 @@ pub struct RxFrameHash {
 #[derive(Default, Hash, Resource, PartialEq, Eq)]
 pub struct FrameHashes(pub [FrameHash; DESYNC_MAX_FRAMES as usize]);
 
-// A collection of confirmed frame hashes we've received from our other player
-// This only works for 1v1.  This would have to be extended to consider all
-// remotes in larger scenarios (I accept pull requests!)
+// Confirmed frame hashes we've received, a ring buffer for every remote player handle
 #[derive(Default, Hash, Resource, PartialEq, Eq)]
-pub struct RxFrameHashes(pub [RxFrameHash; DESYNC_MAX_FRAMES as usize]);
+pub struct RxFrameHashes(pub Vec<[RxFrameHash; DESYNC_MAX_FRAMES as usize]>);
+
+impl RxFrameHashes {
+    /// Buffer of a remote player, handles without one get an empty buffer
+    pub fn of_handle_mut(
+        &mut self,
+        handle: usize,
+    ) -> &mut [RxFrameHash; DESYNC_MAX_FRAMES as usize] {
+        if self.0.len() <= handle {
+            self.0.resize_with(handle + 1, Default::default);
+        }
+        &mut self.0[handle]
+    }
+}
+
+/// How a desync was noticed
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DesyncSource {
+    /// A peer's checksum of a confirmed frame differs from ours
+    PeerChecksum,
+    /// A peer sent two different checksums for the same frame
+    PeerConflict,
+    /// Resimulating a frame whose checksum we already sent gave a different one
+    Resimulation,
+    /// GGRS native desync detection
+    Ggrs,
+    /// A peer noticed a desync and asked for a resynchronization
+    Peer,
+}
+
+/// Raised instead of panicking, the match can't continue as is
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DesyncEvent {
+    pub source: DesyncSource,
+    pub frame: Frame,
+    /// Handle of the remote player we diverged from, if we know who it was
+    pub peer: Option<usize>,
+    pub local_checksum: u64,
+    pub remote_checksum: u64,
+}
+impl fmt::Display for DesyncEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Desync ({:?}) on frame {}, local checksum {} != {}",
+            self.source, self.frame, self.local_checksum, self.remote_checksum
+        )?;
+        if let Some(peer) = self.peer {
+            write!(f, " of player {}", peer)?;
+        }
+        Ok(())
+    }
+}
+
+/// The first desync of the match, if there was one.
+/// Should not be rolled back, a desync can't be undone.
+#[derive(Default, Resource, Clone, Debug)]
+pub struct Desynced {
+    pub desync: Option<DesyncEvent>,
+    pub report_path: Option<PathBuf>,
+}
 
 /// Our desync detector!
-/// Validates the hashes we've received so far against the ones we've calculated ourselves.
-/// If there is a difference, panic.  Your game will probably want to handle this more gracefully.
+/// Validates the hashes we've received so far from every remote player against the ones we've calculated ourselves.
+/// If there is a difference, a `DesyncEvent` is raised.
 pub fn frame_validator(
+    config: Res<GameConfig>,
     mut hashes: ResMut<FrameHashes>,
     mut rx_hashes: ResMut<RxFrameHashes>,
     validatable_frame: Res<ValidatableFrame>,
-    _session: ResMut<Session<GGRSConfig>>,
+    #[cfg(feature = "ggrs_desync_detection")] mut session: Option<ResMut<Session<GGRSConfig>>>,
+    mut desyncs: EventWriter<DesyncEvent>,
 ) {
     #[cfg(feature = "ggrs_desync_detection")]
     {
-        if let Session::P2PSession(s) = session.as_mut() {
+        if let Some(Session::P2PSession(s)) = session.as_deref_mut() {
             let events = s.events().collect::<Vec<GGRSEvent<GGRSConfig>>>();
             for event in events {
                 if let GGRSEvent::DesyncDetected {
-                    /// Frame of the checksums
                     frame,
-                    /// local checksum for the given frame
                     local_checksum,
-                    /// remote checksum for the given frame
                     remote_checksum,
-                    /// remote address of the endpoint.
                     addr,
                 } = event
                 {
-                    let msg = format!(
+                    error!(
                         "Desync on frame {:?}, local checksum {:?} != remote checksum {:?} for address {:?}",
+                        frame, local_checksum, remote_checksum, addr
+                    );
+                    desyncs.send(DesyncEvent {
+                        source: DesyncSource::Ggrs,
                         frame,
-                        local_checksum,
-                        remote_checksum,
-                        addr);
-                    panic!("{}", msg);
+                        peer: config.players.iter().position(|p| p.id == addr),
+                        local_checksum: local_checksum as u64,
+                        remote_checksum: remote_checksum as u64,
+                    });
                 }
             }
         }
     }
-    for (i, rx) in rx_hashes.0.iter_mut().enumerate() {
-        // Check every confirmed frame that has not been validated
-        if rx.frame > 0 && !rx.validated {
-            // Get that same frame in our buffer
-            if let Some(sx) = hashes.0.get_mut(i) {
-                // Make sure it's the exact same frame and also confirmed and not yet validated
-                // and importantly is SAFE to validate
-                if sx.frame == rx.frame
-                    && sx.confirmed
-                    && !sx.validated
-                    && validatable_frame.is_validatable(sx.frame)
-                {
-                    // If this is causing your game to exit, you have a bug!
-                    let checksums_match = sx.rapier_checksum == rx.rapier_checksum;
-                    if !checksums_match {
-                        error!("Failed checksum checks {:?} != {:?}", sx, rx);
-                        panic!("Failed checksum checks {:?} != {:?}", sx, rx);
-                    }
 
-                    // Set both as validated
-                    info!("Frame validated {:?}", sx.frame);
-                    sx.validated = true;
-                    rx.validated = true;
+    let remote_handles: Vec<usize> = (0..config.players.len())
+        .filter(|handle| !config.players[*handle].is_local)
+        .collect();
+    for handle in remote_handles.iter() {
+        for (i, rx) in rx_hashes.of_handle_mut(*handle).iter_mut().enumerate() {
+            // Check every confirmed frame that has not been validated
+            if rx.frame > 0 && !rx.validated {
+                // Get that same frame in our buffer
+                if let Some(sx) = hashes.0.get(i) {
+                    // Make sure it's the exact same frame and also confirmed
+                    // and importantly is SAFE to validate
+                    if sx.frame == rx.frame
+                        && sx.confirmed
+                        && validatable_frame.is_validatable(sx.frame)
+                    {
+                        // If this is ending your game, you have a bug!
+                        let checksums_match = sx.rapier_checksum == rx.rapier_checksum;
+                        if !checksums_match {
+                            error!(
+                                "Failed checksum checks of player {} {:?} != {:?}",
+                                handle, sx, rx
+                            );
+                            desyncs.send(DesyncEvent {
+                                source: DesyncSource::PeerChecksum,
+                                frame: sx.frame,
+                                peer: Some(*handle),
+                                local_checksum: sx.rapier_checksum,
+                                remote_checksum: rx.rapier_checksum,
+                            });
+                        }
+
+                        info!("Frame {:?} validated against player {}", sx.frame, handle);
+                        rx.validated = true;
+                    }
                 }
             }
         }
     }
+
+    // Our frame is validated once every remote player has validated it
+    for (i, sx) in hashes.0.iter_mut().enumerate() {
+        if sx.frame > 0 && sx.confirmed && !sx.validated {
+            sx.validated = remote_handles.iter().all(|handle| {
+                let rx = &rx_hashes.of_handle_mut(*handle)[i];
+                rx.frame == sx.frame && rx.validated
+            });
+        }
+    }
 }
 
 pub fn force_update_rollbackables(
@@ -0,0 +1,42 @@
+use crate::domain::checksum::StateChecksums;
+use crate::domain::desync::DesyncEvent;
+use crate::domain::input_stream::RecordedControls;
+use ggrs::Frame;
+use serde::{Deserialize, Serialize};
+use zoop_shared::player_id::PlayerId;
+
+/// Everything we know locally about a desync, to be compared with the other peers' reports
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DesyncReport {
+    pub desync: DesyncEvent,
+    /// In player handle order
+    pub players: Vec<PlayerId>,
+    pub local_handles: Vec<usize>,
+    /// The remote player we diverged from, if we know who it was
+    pub peer: Option<PlayerId>,
+    /// Frame of the Rapier state, which is past the desynced frame
+    pub current_frame: Frame,
+    pub rapier_checksum: u64,
+    /// Checksums of the parts of the state on the desynced frame, if it's still remembered
+    pub state_checksums: Option<StateChecksums>,
+    /// `PhysicsSnapshot` of the Rapier state
+    pub rapier_state: Vec<u8>,
+    /// Frame of the first recorded inputs
+    pub first_input_frame: Frame,
+    /// Confirmed or predicted inputs of every player, up to the current frame
+    pub inputs: Vec<Vec<RecordedControls>>,
+}
+
+impl DesyncReport {
+    pub fn file_name(&self) -> String {
+        format!("zoop_desync_frame_{}.json", self.desync.frame)
+    }
+
+    /// Parts of the state which differ from another peer's report of the same frame
+    pub fn diverged_from(&self, other: &DesyncReport) -> Vec<&'static str> {
+        match (self.state_checksums, other.state_checksums) {
+            (Some(ours), Some(theirs)) => ours.diverged(&theirs),
+            _ => vec![],
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::domain::controls::Controls;
+use crate::domain::game_config::GameConfig;
+use crate::domain::ggrs_config::GGRSConfig;
+use bevy::prelude::*;
+use bevy_ggrs::PlayerInputs;
+use ggrs::InputStatus;
+
+/// Inputs of every player for the simulated frame.
+/// Decoupled from the GGRS session, so that the simulation can also be stepped without one.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct FrameInputs(pub Vec<(Controls, InputStatus)>);
+
+impl FrameInputs {
+    pub fn confirmed(controls: Vec<Controls>) -> FrameInputs {
+        FrameInputs(
+            controls
+                .into_iter()
+                .map(|controls| (controls, InputStatus::Confirmed))
+                .collect(),
+        )
+    }
+}
+
+/// Takes over the inputs GGRS provides, if there is a session
+pub fn update_frame_inputs(
+    config: Res<GameConfig>,
+    inputs: Option<Res<PlayerInputs<GGRSConfig>>>,
+    mut frame_inputs: ResMut<FrameInputs>,
+) {
+    if let Some(inputs) = inputs {
+        frame_inputs.0 = (0..config.players.len())
+            .map(|handle| inputs[handle])
+            .collect();
+    }
+}
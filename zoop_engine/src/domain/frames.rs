@@ -21,6 +21,12 @@ pub struct CurrentSessionFrame(pub Frame);
 #[reflect(Hash)]
 pub struct CurrentFrame(pub Frame);
 
+/// Our frame on which the current GGRS session started, sessions count their frames from 0.
+/// Non-zero after a session was restarted from a resynchronized state.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Resource, Hash, Reflect)]
+#[reflect(Hash)]
+pub struct SessionStartFrame(pub Frame);
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Resource, Hash, Reflect)]
 #[reflect(Hash)]
 pub struct ValidatableFrame(pub Frame);
@@ -50,14 +56,23 @@ pub struct RollbackStatus {
 pub fn update_confirmed_frame(
     mut confirmed_frame: ResMut<ConfirmedFrame>,
     current_frame: Res<CurrentFrame>,
+    session_start_frame: Res<SessionStartFrame>,
     session: Option<Res<Session<GGRSConfig>>>,
 ) {
     if let Some(session) = session {
         match &*session {
             Session::SyncTestSession(_) => confirmed_frame.0 = current_frame.0,
-            Session::P2PSession(s) => confirmed_frame.0 = s.confirmed_frame(),
+            Session::P2PSession(s) => {
+                confirmed_frame.0 = match s.confirmed_frame() {
+                    NULL_FRAME => session_start_frame.0,
+                    frame => session_start_frame.0 + frame,
+                }
+            }
             Session::SpectatorSession(_) => confirmed_frame.0 = current_frame.0,
         }
+    } else {
+        // Without a session every frame is final
+        confirmed_frame.0 = current_frame.0;
     }
 
     debug!("confirmed frame: {}", confirmed_frame.0);
@@ -71,12 +86,15 @@ pub fn update_current_frame(mut current_frame: ResMut<CurrentFrame>) {
 pub fn update_current_session_frame(
     mut current_session_frame: ResMut<CurrentSessionFrame>,
     current_frame: Res<CurrentFrame>,
+    session_start_frame: Res<SessionStartFrame>,
     session: Option<Res<Session<GGRSConfig>>>,
 ) {
     if let Some(session) = session {
         match &*session {
             Session::SyncTestSession(_) => current_session_frame.0 = current_frame.0,
-            Session::P2PSession(s) => current_session_frame.0 = s.current_frame(),
+            Session::P2PSession(s) => {
+                current_session_frame.0 = session_start_frame.0 + s.current_frame()
+            }
             Session::SpectatorSession(_) => current_session_frame.0 = current_frame.0,
         }
     }
@@ -1,8 +1,12 @@
-use crate::domain::room_config::RoomConfig;
+use crate::domain::game_mode::GameMode;
+use crate::domain::key_bindings::KeyBindings;
+use crate::domain::track::TrackDefinition;
 use crate::logic::math::*;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use url::{ParseError, Url};
+use std::path::PathBuf;
+use zoop_shared::car_class::CarClass;
+use zoop_shared::car_tuning::CarTuning;
 use zoop_shared::network_player::NetworkPlayer;
 
 #[derive(Resource, Clone, Debug)]
@@ -11,12 +15,29 @@ pub struct GameConfig {
     // - distance in pixels
     // - angles in radians
     // - percentages from 0.0 to 1.0
-    pub network: RoomConfig,
+    pub mode: GameMode,
     pub players: Vec<NetworkPlayer>,
+    /// By player handle, players without one drive the default class
+    pub car_classes: Vec<CarClass>,
+    pub spectators: Vec<NetworkPlayer>,
     pub fps: u16,
     pub load_seconds: u16,
+    pub countdown_seconds: u16,
+    pub false_start_penalty_seconds: u16,
+    pub race_finish_grace_seconds: u16,
+    /// Rounds of a match, whoever wins most of them wins the match
+    pub best_of: u16,
     pub canvas_selector: Option<String>,
+    pub key_bindings: KeyBindings,
+    pub replay_file: Option<PathBuf>,
+    /// Where desync reports are saved, they are only handed out through interop if omitted
+    pub diagnostics_dir: Option<PathBuf>,
+    pub track: TrackDefinition,
     pub pixels_per_meter: f32,
+    pub camera_smoothing: f32,
+    pub camera_look_ahead_seconds: f32,
+    pub camera_zoom_per_speed: f32,
+    pub camera_max_zoom: f32,
     pub car_half_width: f32,
     pub car_half_length: f32,
     pub tire_half_thickness: f32,
@@ -27,9 +48,25 @@ pub struct GameConfig {
     pub tire_reversing_force: f32,
     pub tire_breaking_force: f32,
     pub tire_friction_force: f32,
+    pub tire_half_grip_slip_angle: f32,
+    pub tire_handbrake_grip: f32,
     pub tire_linear_damping: f32,
     pub tire_angular_damping: f32,
+    /// Tuning the handling constants were applied from, peers compare its fingerprint
+    pub tuning: CarTuning,
+    /// Replace what the car classes do when set, see `CarTuning`
+    pub drive_front_share: Option<f32>,
+    pub rear_steering_ratio: Option<f32>,
+    /// Contacts pushing a car with less force than this don't damage it
+    pub collision_force_threshold: f32,
+    pub collision_damage_per_impulse: f32,
+    /// Share of the drive force a wrecked car loses
+    pub damage_max_power_loss: f32,
+    /// Angle the front wheels of a wrecked car are bent by
+    pub damage_max_steering_bend: f32,
     pub desync_max_frames: u16,
+    pub max_resyncs: u16,
+    pub resync_timeout_seconds: u16,
 }
 
 pub const DESYNC_MAX_FRAMES: u16 = 30;
@@ -40,45 +77,110 @@ impl GameConfig {
     }
 
     pub fn default(
-        network: RoomConfig,
+        mode: GameMode,
         players: Vec<NetworkPlayer>,
         canvas_selector: Option<String>,
     ) -> GameConfig {
         let ppm = 10.0;
         let m2p = |meters: f32| GameConfig::_meters2pix(ppm, meters);
+        let tuning = CarTuning::default();
         GameConfig {
-            network,
+            mode,
             players,
+            car_classes: vec![],
+            spectators: vec![],
             fps: 60,
             load_seconds: 1,
+            countdown_seconds: 3,
+            false_start_penalty_seconds: 2,
+            race_finish_grace_seconds: 30,
+            best_of: 1,
             canvas_selector,
+            key_bindings: KeyBindings::default(),
+            replay_file: None,
+            diagnostics_dir: None,
+            track: TrackDefinition::default(),
             pixels_per_meter: ppm,
+            camera_smoothing: 5.0,
+            camera_look_ahead_seconds: 0.3,
+            camera_zoom_per_speed: 0.02,
+            camera_max_zoom: 1.6,
             car_half_width: m2p(1.0),
             car_half_length: m2p(2.0),
             tire_half_thickness: m2p(0.2),
             tire_radius: m2p(0.4),
-            tire_rotation_per_tick: deg2rad(15.0),
-            tire_max_angle: deg2rad(35.0),
-            tire_acceleration_force: m2p(140.0),
-            tire_reversing_force: m2p(100.0),
-            tire_breaking_force: m2p(300.0),
-            tire_friction_force: 0.5,
-            tire_linear_damping: 5.0,
-            tire_angular_damping: 0.1,
+            tire_rotation_per_tick: deg2rad(tuning.steering_degrees_per_tick),
+            tire_max_angle: deg2rad(tuning.max_steering_degrees),
+            tire_acceleration_force: m2p(tuning.acceleration_force),
+            tire_reversing_force: m2p(tuning.reversing_force),
+            tire_breaking_force: m2p(tuning.breaking_force),
+            tire_friction_force: tuning.friction_force,
+            tire_half_grip_slip_angle: deg2rad(tuning.half_grip_slip_degrees),
+            tire_handbrake_grip: tuning.handbrake_grip,
+            tire_linear_damping: tuning.linear_damping,
+            tire_angular_damping: tuning.angular_damping,
+            drive_front_share: tuning.drive_front_share,
+            rear_steering_ratio: tuning.rear_steering_ratio,
+            tuning,
+            collision_force_threshold: 500.0,
+            collision_damage_per_impulse: 0.002,
+            damage_max_power_loss: 0.5,
+            damage_max_steering_bend: deg2rad(6.0),
             desync_max_frames: DESYNC_MAX_FRAMES,
+            max_resyncs: 3,
+            resync_timeout_seconds: 10,
         }
     }
 
-    pub fn game_room_address(&self) -> Result<Url, ParseError> {
-        self.network.server_address.join(
-            format!(
-                "/api/game/connect/{}/as/{}/ticket/{}",
-                self.network.room.0.to_string(),
-                self.network.user_id.0.to_string(),
-                self.network.user_ticket
-            )
-            .as_str(),
-        )
+    /// Overrides the handling constants, the tuning has to be validated already
+    pub fn apply_tuning(&mut self, tuning: &CarTuning) {
+        let ppm = self.pixels_per_meter;
+        let m2p = |meters: f32| GameConfig::_meters2pix(ppm, meters);
+        self.tire_rotation_per_tick = deg2rad(tuning.steering_degrees_per_tick);
+        self.tire_max_angle = deg2rad(tuning.max_steering_degrees);
+        self.tire_acceleration_force = m2p(tuning.acceleration_force);
+        self.tire_reversing_force = m2p(tuning.reversing_force);
+        self.tire_breaking_force = m2p(tuning.breaking_force);
+        self.tire_friction_force = tuning.friction_force;
+        self.tire_half_grip_slip_angle = deg2rad(tuning.half_grip_slip_degrees);
+        self.tire_handbrake_grip = tuning.handbrake_grip;
+        self.tire_linear_damping = tuning.linear_damping;
+        self.tire_angular_damping = tuning.angular_damping;
+        self.drive_front_share = tuning.drive_front_share;
+        self.rear_steering_ratio = tuning.rear_steering_ratio;
+        self.tuning = tuning.clone();
+    }
+
+    pub fn car_class(&self, handle: usize) -> CarClass {
+        self.car_classes.get(handle).copied().unwrap_or_default()
+    }
+
+    /// Handle of the car shown on screen, spectators watch the host
+    pub fn followed_handle(&self) -> usize {
+        self.players
+            .iter()
+            .position(|player| player.is_local)
+            .unwrap_or(0)
+    }
+
+    pub fn is_spectating(&self) -> bool {
+        self.spectators.iter().any(|spectator| spectator.is_local)
+    }
+
+    /// The first player relays inputs to spectators
+    pub fn is_host(&self) -> bool {
+        self.players
+            .first()
+            .map(|player| player.is_local)
+            .unwrap_or(false)
+    }
+
+    /// Races are recorded unless they are a replay themselves
+    pub fn is_recording(&self) -> bool {
+        match self.mode {
+            GameMode::Networked(_) | GameMode::SyncTest(_) => true,
+            GameMode::Headless | GameMode::Replay => false,
+        }
     }
 
     pub fn tire_damping(&self) -> Damping {
@@ -0,0 +1,15 @@
+use bevy::prelude::*;
+
+const EMBEDDED_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono-Bold.ttf");
+
+/// Font embedded in the binary, so that text also renders without an asset server
+#[derive(Resource, Clone, Debug)]
+pub struct GameFont(pub Handle<Font>);
+
+impl GameFont {
+    pub fn load(fonts: &mut Assets<Font>) -> GameFont {
+        let font =
+            Font::try_from_bytes(EMBEDDED_FONT.to_vec()).expect("Embedded font must be valid");
+        GameFont(fonts.add(font))
+    }
+}
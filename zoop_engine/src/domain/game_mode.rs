@@ -0,0 +1,15 @@
+use crate::domain::room_config::RoomConfig;
+use crate::domain::sync_test::SyncTestConfig;
+
+/// Where the game gets its players and inputs from
+#[derive(Clone, Debug)]
+pub enum GameMode {
+    /// Peer-to-peer race through the game server
+    Networked(RoomConfig),
+    /// Local determinism check, every frame is resimulated and compared
+    SyncTest(SyncTestConfig),
+    /// Simulation stepped by the caller, without a window or session
+    Headless,
+    /// Playback of a recorded race, stepped frame by frame from its inputs
+    Replay,
+}
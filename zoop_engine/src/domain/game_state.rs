@@ -1,16 +1,26 @@
 use crate::domain::car::tire_position;
+use crate::domain::car_class::CarSpec;
+use crate::domain::damage::CarDamage;
 use crate::domain::player::Player;
+use crate::domain::race::{MatchState, RaceProgress, RaceState};
+use crate::domain::rapier_serde::*;
+use crate::domain::surface::SurfaceKind;
 use crate::domain::tire::TirePhysics;
+use crate::domain::track::TrackWallKind;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Default, Resource, Reflect, FromReflect)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Resource, Reflect, FromReflect)]
 #[reflect(Resource)]
 pub struct EntityPhysics {
     pub transform: Transform,
     pub velocity: Velocity,
+    #[serde(with = "ExternalForceDef")]
     pub force: ExternalForce,
+    #[serde(with = "ExternalImpulseDef")]
     pub impulse: ExternalImpulse,
+    #[serde(with = "ReadMassPropertiesDef")]
     pub mass: ReadMassProperties,
 }
 impl EntityPhysics {
@@ -29,9 +39,10 @@ impl EntityPhysics {
             mass,
         }
     }
-    pub fn fixed(position: Vec3) -> EntityPhysics {
+    pub fn fixed(position: Vec3, angle: f32) -> EntityPhysics {
         EntityPhysics {
-            transform: Transform::from_translation(position),
+            transform: Transform::from_translation(position)
+                .with_rotation(Quat::from_rotation_z(angle)),
             velocity: Velocity::zero(),
             force: ExternalForce::default(),
             impulse: ExternalImpulse::default(),
@@ -40,7 +51,7 @@ impl EntityPhysics {
     }
 }
 
-#[derive(Clone, Debug, Default, Resource, Reflect, FromReflect)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Resource, Reflect, FromReflect)]
 #[reflect(Resource)]
 pub struct GameTire {
     pub tire_physics: TirePhysics,
@@ -60,15 +71,15 @@ impl GameTire {
             entity_physics: EntityPhysics::of(transform, velocity, force, impulse, mass),
         }
     }
-    pub fn fixed(position: Vec3) -> GameTire {
+    pub fn fixed(position: Vec3, angle: f32) -> GameTire {
         GameTire {
             tire_physics: TirePhysics::default(),
-            entity_physics: EntityPhysics::fixed(position),
+            entity_physics: EntityPhysics::fixed(position, angle),
         }
     }
 }
 
-#[derive(Clone, Debug, Default, Resource, Reflect, FromReflect)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Resource, Reflect, FromReflect)]
 #[reflect(Resource)]
 pub struct GameCar {
     pub tire_top_left: GameTire,
@@ -77,63 +88,145 @@ pub struct GameCar {
     pub tire_bottom_right: GameTire,
     pub physics: EntityPhysics,
     pub player: Player,
+    pub progress: RaceProgress,
+    pub spec: CarSpec,
+    pub damage: CarDamage,
 }
 impl GameCar {
-    pub fn fixed_for_player(
-        player: Player,
-        position: Vec3,
-        car_half_size: Vec2,
-        tire_half_size: Vec2,
-    ) -> GameCar {
+    pub fn fixed_for_player(player: Player, position: Vec3, angle: f32, spec: CarSpec) -> GameCar {
+        let car_half_size = spec.half_size;
+        let tire_half_size = spec.tire_half_size;
         GameCar {
-            tire_top_left: GameTire::fixed(tire_position(
-                position,
-                car_half_size,
-                tire_half_size,
-                true,
-                false,
-            )),
-            tire_top_right: GameTire::fixed(tire_position(
-                position,
-                car_half_size,
-                tire_half_size,
-                true,
-                true,
-            )),
-            tire_bottom_left: GameTire::fixed(tire_position(
-                position,
-                car_half_size,
-                tire_half_size,
-                false,
-                false,
-            )),
-            tire_bottom_right: GameTire::fixed(tire_position(
-                position,
-                car_half_size,
-                tire_half_size,
-                false,
-                true,
-            )),
-            physics: EntityPhysics::fixed(position),
+            tire_top_left: GameTire::fixed(
+                tire_position(position, angle, car_half_size, tire_half_size, true, false),
+                angle,
+            ),
+            tire_top_right: GameTire::fixed(
+                tire_position(position, angle, car_half_size, tire_half_size, true, true),
+                angle,
+            ),
+            tire_bottom_left: GameTire::fixed(
+                tire_position(position, angle, car_half_size, tire_half_size, false, false),
+                angle,
+            ),
+            tire_bottom_right: GameTire::fixed(
+                tire_position(position, angle, car_half_size, tire_half_size, false, true),
+                angle,
+            ),
+            physics: EntityPhysics::fixed(position, angle),
             player,
+            progress: RaceProgress::default(),
+            spec,
+            damage: CarDamage::default(),
         }
     }
+
+    pub fn tire(&self, is_front: bool, is_right: bool) -> &GameTire {
+        match (is_front, is_right) {
+            (true, false) => &self.tire_top_left,
+            (true, true) => &self.tire_top_right,
+            (false, false) => &self.tire_bottom_left,
+            (false, true) => &self.tire_bottom_right,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Resource, Reflect, FromReflect)]
+#[reflect(Resource)]
+pub struct GameWall {
+    pub kind: TrackWallKind,
+    pub transform: Transform,
+    pub half_size: Vec2,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Resource, Reflect, FromReflect)]
+#[reflect(Resource)]
+pub struct GameGround {
+    pub surface: SurfaceKind,
+    pub transform: Transform,
+    pub half_size: Vec2,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Resource, Reflect, FromReflect)]
+#[reflect(Resource)]
+pub struct GameCheckpoint {
+    /// Order in which checkpoints have to be crossed, ignored for the finish line
+    pub index: u16,
+    pub is_finish: bool,
+    pub transform: Transform,
+    pub half_size: Vec2,
 }
 
-#[derive(Clone, Debug, Resource, Reflect, FromReflect)]
+#[derive(Serialize, Deserialize, Clone, Debug, Resource, Reflect, FromReflect)]
 #[reflect(Resource)]
 pub enum GameEntity {
     Stub(),
     Car(GameCar),
+    Wall(GameWall),
+    Ground(GameGround),
+    Checkpoint(GameCheckpoint),
 }
 impl Default for GameEntity {
     fn default() -> Self {
         GameEntity::Stub()
     }
 }
+impl GameEntity {
+    /// Entities spawned for this one, a car has a body and four tires
+    pub fn spawn_count(&self) -> usize {
+        match self {
+            GameEntity::Stub() => 0,
+            GameEntity::Car(_) => 5,
+            GameEntity::Wall(_) | GameEntity::Ground(_) | GameEntity::Checkpoint(_) => 1,
+        }
+    }
+}
 
-#[derive(Clone, Debug, Default, Resource, Reflect, FromReflect)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Resource, Reflect, FromReflect)]
 #[reflect(Resource)]
 pub struct GameState {
     pub entities: Vec<GameEntity>,
+    pub race: RaceState,
+    #[serde(rename = "match")]
+    pub match_state: MatchState,
+}
+impl GameState {
+    /// Entities needed to spawn the whole scene
+    pub fn spawn_count(&self) -> usize {
+        self.entities.iter().map(GameEntity::spawn_count).sum()
+    }
+
+    pub fn cars(&self) -> impl Iterator<Item = &GameCar> {
+        self.entities.iter().filter_map(|e| match e {
+            GameEntity::Car(car) => Some(car),
+            _ => None,
+        })
+    }
+
+    pub fn cars_mut(&mut self) -> impl Iterator<Item = &mut GameCar> {
+        self.entities.iter_mut().filter_map(|e| match e {
+            GameEntity::Car(car) => Some(car),
+            _ => None,
+        })
+    }
+
+    /// Cars ordered by race position, finished cars first in the order they finished
+    pub fn standings(&self) -> Vec<&GameCar> {
+        let mut cars: Vec<&GameCar> = self.cars().collect();
+        cars.sort_by_key(|car| {
+            let finish_position = self
+                .race
+                .finish_order
+                .iter()
+                .position(|handle| *handle == car.player.handle)
+                .unwrap_or(usize::MAX);
+            (
+                finish_position,
+                std::cmp::Reverse(car.progress.laps),
+                std::cmp::Reverse(car.progress.next_checkpoint),
+                car.player.handle,
+            )
+        });
+        cars
+    }
 }
@@ -0,0 +1,72 @@
+use crate::domain::game_config::GameConfig;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Seconds between handshakes, peers which haven't connected yet miss them
+pub const HANDSHAKE_RESEND_SECONDS: f32 = 0.5;
+
+/// Sent to every other player until the session starts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HandshakeMessage {
+    pub tuning_fingerprint: String,
+    /// Answers a handshake, so that peers which started already are still heard from
+    pub is_reply: bool,
+}
+
+/// A player would simulate with different handling than we do
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TuningMismatch {
+    pub handle: usize,
+    pub local_fingerprint: String,
+    pub remote_fingerprint: String,
+}
+impl fmt::Display for TuningMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Player {} drives with car tuning {}, but ours is {}, not starting the match",
+            self.handle, self.remote_fingerprint, self.local_fingerprint
+        )
+    }
+}
+
+/// Agreement on the car tuning before the session starts, should not be rolled back
+#[derive(Resource, Clone, Debug)]
+pub struct TuningHandshake {
+    pub fingerprint: String,
+    /// Fingerprints of the other players by handle
+    pub received: HashMap<usize, String>,
+    /// Seconds since the handshake was last sent
+    pub since_sent: f32,
+    pub started: bool,
+    pub mismatch: Option<TuningMismatch>,
+}
+
+impl TuningHandshake {
+    pub fn of(config: &GameConfig) -> TuningHandshake {
+        TuningHandshake {
+            fingerprint: config.tuning.fingerprint(),
+            received: HashMap::new(),
+            // Sent right away
+            since_sent: HANDSHAKE_RESEND_SECONDS,
+            started: false,
+            mismatch: None,
+        }
+    }
+
+    /// The first player whose fingerprint differs from ours, in handle order
+    pub fn find_mismatch(&self) -> Option<TuningMismatch> {
+        let mut handles: Vec<&usize> = self.received.keys().collect();
+        handles.sort();
+        handles.into_iter().find_map(|handle| {
+            let remote_fingerprint = &self.received[handle];
+            (*remote_fingerprint != self.fingerprint).then(|| TuningMismatch {
+                handle: *handle,
+                local_fingerprint: self.fingerprint.clone(),
+                remote_fingerprint: remote_fingerprint.clone(),
+            })
+        })
+    }
+}
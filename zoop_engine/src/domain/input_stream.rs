@@ -0,0 +1,128 @@
+use crate::domain::controls::Controls;
+use crate::domain::sync_test::SyncTestInputs;
+use ggrs::Frame;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Serializable part of `Controls`, without the desync detection piggyback
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecordedControls {
+    pub input: u16,
+    pub steering: i8,
+    pub throttle: u8,
+    pub reverse: u8,
+    pub brake: u8,
+}
+
+impl From<Controls> for RecordedControls {
+    fn from(controls: Controls) -> Self {
+        RecordedControls {
+            input: controls.input,
+            steering: controls.steering,
+            throttle: controls.throttle,
+            reverse: controls.reverse,
+            brake: controls.brake,
+        }
+    }
+}
+
+impl RecordedControls {
+    pub fn controls(&self) -> Controls {
+        Controls {
+            input: self.input,
+            steering: self.steering,
+            throttle: self.throttle,
+            reverse: self.reverse,
+            brake: self.brake,
+            ..Controls::empty(0, ggrs::NULL_FRAME)
+        }
+    }
+}
+
+/// Controls of every player for consecutive frames, starting from frame 1
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct InputStream {
+    pub player_count: usize,
+    pub frames: Vec<Vec<RecordedControls>>,
+}
+
+#[derive(Debug)]
+pub enum InputStreamError {
+    Io(String),
+    Parse(String),
+}
+impl fmt::Display for InputStreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputStreamError::Io(e) => write!(f, "Failed to read input stream: {}", e),
+            InputStreamError::Parse(e) => write!(f, "Failed to parse input stream: {}", e),
+        }
+    }
+}
+
+impl InputStream {
+    pub fn from_json(json: &str) -> Result<InputStream, InputStreamError> {
+        serde_json::from_str(json).map_err(|e| InputStreamError::Parse(e.to_string()))
+    }
+
+    pub fn from_file(path: &Path) -> Result<InputStream, InputStreamError> {
+        let json =
+            std::fs::read_to_string(path).map_err(|e| InputStreamError::Io(e.to_string()))?;
+        InputStream::from_json(&json)
+    }
+
+    /// Pre-generates scripted or random inputs
+    pub fn generate(inputs: &SyncTestInputs, player_count: usize, frames: Frame) -> InputStream {
+        InputStream {
+            player_count,
+            frames: (1..=frames)
+                .map(|frame| {
+                    (0..player_count)
+                        .map(|handle| {
+                            inputs
+                                .controls(handle, frame, 0, ggrs::NULL_FRAME)
+                                .map(RecordedControls::from)
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn frame_count(&self) -> Frame {
+        self.frames.len() as Frame
+    }
+
+    /// Controls of every player on a frame, idle past the end of the stream
+    pub fn controls(&self, frame: Frame) -> Vec<Controls> {
+        let recorded = usize::try_from(frame - 1)
+            .ok()
+            .and_then(|index| self.frames.get(index));
+        (0..self.player_count)
+            .map(|handle| {
+                recorded
+                    .and_then(|controls| controls.get(handle))
+                    .copied()
+                    .unwrap_or_default()
+                    .controls()
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadlessResult {
+    pub frames: Frame,
+    pub rapier_checksum: u64,
+}
+impl fmt::Display for HeadlessResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Simulated {} frames, final Rapier checksum {}",
+            self.frames, self.rapier_checksum
+        )
+    }
+}
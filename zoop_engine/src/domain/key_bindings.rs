@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Keyboard layout of the local player, never sent over the network
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub accelerate: KeyCode,
+    pub reverse: KeyCode,
+    pub brake: KeyCode,
+    pub steer_right: KeyCode,
+    pub steer_left: KeyCode,
+    pub handbrake: KeyCode,
+    /// Races the next round once the race is over
+    pub rematch: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            accelerate: KeyCode::W,
+            reverse: KeyCode::S,
+            brake: KeyCode::C,
+            steer_right: KeyCode::D,
+            steer_left: KeyCode::A,
+            handbrake: KeyCode::Space,
+            rematch: KeyCode::Return,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum KeyBindingsError {
+    Io(String),
+    Parse(String),
+}
+impl fmt::Display for KeyBindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyBindingsError::Io(e) => write!(f, "Failed to read key bindings file: {}", e),
+            KeyBindingsError::Parse(e) => write!(f, "Failed to parse key bindings: {}", e),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn from_json(json: &str) -> Result<KeyBindings, KeyBindingsError> {
+        serde_json::from_str(json).map_err(|e| KeyBindingsError::Parse(e.to_string()))
+    }
+
+    pub fn from_file(path: &Path) -> Result<KeyBindings, KeyBindingsError> {
+        let json =
+            std::fs::read_to_string(path).map_err(|e| KeyBindingsError::Io(e.to_string()))?;
+        KeyBindings::from_json(&json)
+    }
+
+    /// Native settings file location, e.g. `~/.config/zoop/key_bindings.json`
+    pub fn settings_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("zoop").join("key_bindings.json"))
+    }
+
+    /// Loads the native settings file, if the player has created one
+    pub fn from_settings() -> Result<Option<KeyBindings>, KeyBindingsError> {
+        match KeyBindings::settings_path() {
+            Some(path) if path.exists() => KeyBindings::from_file(&path).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
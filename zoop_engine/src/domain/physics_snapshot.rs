@@ -0,0 +1,261 @@
+use bevy_rapier2d::plugin::RapierContext;
+use bevy_rapier2d::rapier::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Marks snapshot bytes, so that they aren't mistaken for a bincode serialized `RapierContext`
+const SNAPSHOT_MAGIC: &[u8; 4] = b"ZPS2";
+
+/// Everything the next step depends on: bodies, colliders, joints, the contact graph with its
+/// warm-start impulses, islands with their sleep state, the broad phase and CCD.
+/// The query pipeline is rebuilt after restoring instead and the integration parameters
+/// never change, so neither is part of the snapshot.
+///
+/// Body state alone (poses, velocities and joint impulses) can't resimulate a frame bit for bit.
+/// Rapier warm-starts contacts with the impulses kept in the contact graph, solves them in the
+/// order of that graph and of the islands' active bodies, and the broad phase only reports pairs
+/// which changed since its last update. Bodies restored next to the contact graph, islands and
+/// broad phase of a later frame end up a few bits off, which peers see as a desync.
+pub struct PhysicsSnapshot;
+
+/// Where `save_into` wrote the parts which `StateChecksums` tells apart
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotLayout {
+    pub bodies: Range<usize>,
+    pub colliders: Range<usize>,
+    pub joints: Range<usize>,
+}
+
+/// Same fields in the same order as `save_into` writes them
+#[derive(Deserialize)]
+struct Snapshot {
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+}
+
+fn write_part<T: Serialize>(bytes: &mut Vec<u8>, part: &T) -> Option<Range<usize>> {
+    let start = bytes.len();
+    bincode::serialize_into(&mut *bytes, part).ok()?;
+    Some(start..bytes.len())
+}
+
+impl PhysicsSnapshot {
+    /// Saves into an existing buffer, so that its allocation is reused
+    pub fn save_into(rapier: &RapierContext, bytes: &mut Vec<u8>) -> Option<SnapshotLayout> {
+        bytes.clear();
+        bytes.extend_from_slice(SNAPSHOT_MAGIC);
+        let layout = PhysicsSnapshot::write_parts(rapier, bytes);
+        if layout.is_none() {
+            bytes.clear();
+        }
+        layout
+    }
+
+    /// Bincode writes a struct as its fields one after another, so this reads back as `Snapshot`
+    fn write_parts(rapier: &RapierContext, bytes: &mut Vec<u8>) -> Option<SnapshotLayout> {
+        let bodies = write_part(bytes, &rapier.bodies)?;
+        let colliders = write_part(bytes, &rapier.colliders)?;
+        write_part(bytes, &rapier.islands)?;
+        write_part(bytes, &rapier.broad_phase)?;
+        write_part(bytes, &rapier.narrow_phase)?;
+        let impulse_joints = write_part(bytes, &rapier.impulse_joints)?;
+        let multibody_joints = write_part(bytes, &rapier.multibody_joints)?;
+        write_part(bytes, &rapier.ccd_solver)?;
+        Some(SnapshotLayout {
+            bodies,
+            colliders,
+            joints: impulse_joints.start..multibody_joints.end,
+        })
+    }
+
+    pub fn save(rapier: &RapierContext) -> Vec<u8> {
+        let mut bytes = vec![];
+        PhysicsSnapshot::save_into(rapier, &mut bytes);
+        bytes
+    }
+
+    /// Restores into the existing context, returns whether the snapshot could be read
+    pub fn restore(rapier: &mut RapierContext, bytes: &[u8]) -> bool {
+        let Some(snapshot) = bytes
+            .strip_prefix(SNAPSHOT_MAGIC.as_slice())
+            .and_then(|bytes| bincode::deserialize::<Snapshot>(bytes).ok())
+        else {
+            return false;
+        };
+
+        rapier.bodies = snapshot.bodies;
+        rapier.colliders = snapshot.colliders;
+        rapier.islands = snapshot.islands;
+        rapier.broad_phase = snapshot.broad_phase;
+        rapier.narrow_phase = snapshot.narrow_phase;
+        rapier.impulse_joints = snapshot.impulse_joints;
+        rapier.multibody_joints = snapshot.multibody_joints;
+        rapier.ccd_solver = snapshot.ccd_solver;
+
+        // Only answers scene queries, which the step doesn't depend on
+        rapier
+            .query_pipeline
+            .update(&rapier.bodies, &rapier.colliders);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::checksum::fnv1a64;
+
+    /// Two boxes driving into each other and onto a wall, so that contacts and islands change
+    fn colliding_context() -> RapierContext {
+        let mut rapier = RapierContext::default();
+        let wall = ColliderBuilder::cuboid(20.0, 0.5)
+            .translation(vector![0.0, -3.0])
+            .build();
+        rapier.colliders.insert(wall);
+        for (x, velocity) in [(-4.0, 8.0), (4.0, -8.0)] {
+            let body = rapier.bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(vector![x, 0.0])
+                    .linvel(vector![velocity, -2.0])
+                    .build(),
+            );
+            rapier.colliders.insert_with_parent(
+                ColliderBuilder::cuboid(1.0, 2.0).build(),
+                body,
+                &mut rapier.bodies,
+            );
+        }
+        rapier
+    }
+
+    fn step(rapier: &mut RapierContext, pipeline: &mut PhysicsPipeline, frames: usize) {
+        for _ in 0..frames {
+            pipeline.step(
+                &Vector::zeros(),
+                &rapier.integration_parameters,
+                &mut rapier.islands,
+                &mut rapier.broad_phase,
+                &mut rapier.narrow_phase,
+                &mut rapier.bodies,
+                &mut rapier.colliders,
+                &mut rapier.impulse_joints,
+                &mut rapier.multibody_joints,
+                &mut rapier.ccd_solver,
+                Some(&mut rapier.query_pipeline),
+                &(),
+                &(),
+            );
+        }
+    }
+
+    fn checksum(rapier: &RapierContext) -> u64 {
+        fnv1a64(&PhysicsSnapshot::save(rapier))
+    }
+
+    #[test]
+    fn restored_snapshot_resimulates_identically() {
+        let mut rapier = colliding_context();
+        let mut pipeline = PhysicsPipeline::new();
+
+        // The boxes touch the wall and each other while resimulating
+        step(&mut rapier, &mut pipeline, 20);
+        let snapshot = PhysicsSnapshot::save(&rapier);
+        step(&mut rapier, &mut pipeline, 30);
+        let expected = checksum(&rapier);
+        assert!(rapier
+            .narrow_phase
+            .contact_pairs()
+            .any(|pair| pair.has_any_active_contact));
+
+        assert!(PhysicsSnapshot::restore(&mut rapier, &snapshot));
+        step(&mut rapier, &mut pipeline, 30);
+        assert_eq!(checksum(&rapier), expected);
+    }
+
+    /// Whether the box touched the line on each frame of a round
+    fn race_through_finish_line(
+        rapier: &mut RapierContext,
+        pipeline: &mut PhysicsPipeline,
+        car: ColliderHandle,
+        finish_line: ColliderHandle,
+    ) -> Vec<bool> {
+        (0..60)
+            .map(|_| {
+                step(rapier, pipeline, 1);
+                rapier.narrow_phase.intersection_pair(car, finish_line) == Some(true)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn restored_round_start_crosses_the_finish_line_again() {
+        let mut rapier = RapierContext::default();
+        let finish_line = rapier
+            .colliders
+            .insert(ColliderBuilder::cuboid(0.25, 5.0).sensor(true).build());
+        let body = rapier.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector![-4.0, 0.0])
+                .linvel(vector![10.0, 0.0])
+                .build(),
+        );
+        let car = rapier.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(1.0, 2.0).build(),
+            body,
+            &mut rapier.bodies,
+        );
+        let mut pipeline = PhysicsPipeline::new();
+        let round_start = PhysicsSnapshot::save(&rapier);
+
+        // The round ends with the box stopped on the line, its pair with the line still reported
+        let first_round = race_through_finish_line(&mut rapier, &mut pipeline, car, finish_line);
+        assert!(first_round.contains(&true));
+        let stopped = rapier.bodies.get_mut(body).unwrap();
+        stopped.set_translation(vector![0.0, 0.0], true);
+        stopped.set_linvel(vector![0.0, 0.0], true);
+        step(&mut rapier, &mut pipeline, 1);
+        assert_eq!(
+            rapier.narrow_phase.intersection_pair(car, finish_line),
+            Some(true)
+        );
+
+        assert!(PhysicsSnapshot::restore(&mut rapier, &round_start));
+        let second_round = race_through_finish_line(&mut rapier, &mut pipeline, car, finish_line);
+        assert_eq!(second_round, first_round);
+    }
+
+    #[test]
+    fn layout_points_at_the_serialized_parts() {
+        let mut rapier = colliding_context();
+        step(&mut rapier, &mut PhysicsPipeline::new(), 20);
+        let mut bytes = vec![];
+        let layout = PhysicsSnapshot::save_into(&rapier, &mut bytes).unwrap();
+
+        assert_eq!(
+            bytes[layout.bodies],
+            bincode::serialize(&rapier.bodies).unwrap()
+        );
+        assert_eq!(
+            bytes[layout.colliders],
+            bincode::serialize(&rapier.colliders).unwrap()
+        );
+        assert_eq!(
+            bytes[layout.joints],
+            bincode::serialize(&(&rapier.impulse_joints, &rapier.multibody_joints)).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_other_bytes() {
+        let mut rapier = colliding_context();
+        let context_bytes = bincode::serialize(&rapier).unwrap();
+        assert!(!PhysicsSnapshot::restore(&mut rapier, &context_bytes));
+        assert!(!PhysicsSnapshot::restore(&mut rapier, SNAPSHOT_MAGIC));
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Default, Component, Reflect, FromReflect)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Component, Reflect, FromReflect)]
 pub struct Player {
     pub handle: usize,
 }
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use ggrs::Frame;
+use serde::{Deserialize, Serialize};
+
+/// Per-player race progress, rolled back as part of the game state
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, Reflect, FromReflect, PartialEq, Eq,
+)]
+pub struct RaceProgress {
+    /// Completed laps
+    pub laps: u16,
+    /// Index of the checkpoint which has to be crossed next.
+    /// Equals the checkpoint count when only the finish line is left.
+    pub next_checkpoint: u16,
+    /// Was the car touching the finish line on the previous frame
+    pub on_finish: bool,
+    pub finished: bool,
+    pub finished_frame: Frame,
+    /// Throttle was pressed during the countdown
+    pub false_start: bool,
+}
+
+impl RaceProgress {
+    /// Controls are ignored before the start and for a while after it when false starting
+    pub fn is_held(&self, frame: Frame, start_frame: Frame, penalty_frames: Frame) -> bool {
+        frame < start_frame || (self.false_start && frame < start_frame + penalty_frames)
+    }
+
+    pub fn advance(
+        &mut self,
+        touches_checkpoint: impl Fn(u16) -> bool,
+        touches_finish: bool,
+        checkpoint_count: u16,
+        laps: u16,
+        frame: Frame,
+    ) {
+        if !self.finished {
+            if self.next_checkpoint < checkpoint_count && touches_checkpoint(self.next_checkpoint) {
+                self.next_checkpoint += 1;
+            }
+
+            // Only count the frame on which the car enters the finish line
+            let entered_finish = touches_finish && !self.on_finish;
+            if entered_finish && self.next_checkpoint == checkpoint_count {
+                self.laps += 1;
+                self.next_checkpoint = 0;
+                if self.laps >= laps {
+                    self.finished = true;
+                    self.finished_frame = frame;
+                }
+            }
+        }
+        self.on_finish = touches_finish;
+    }
+}
+
+/// Race-wide state, rolled back as part of the game state
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Reflect, FromReflect)]
+pub struct RaceState {
+    /// Player handles in the order they finished the race
+    pub finish_order: Vec<usize>,
+    pub is_over: bool,
+    pub over_frame: Frame,
+    /// Player handles which want to race the next round, once the race is over
+    pub rematch_votes: Vec<usize>,
+}
+
+/// Rounds of a best-of-N match, rolled back as part of the game state and kept across rounds
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Reflect, FromReflect, PartialEq, Eq)]
+pub struct MatchState {
+    /// Starting from 1
+    pub round: u16,
+    /// Won rounds by player handle
+    pub wins: Vec<u16>,
+}
+
+impl MatchState {
+    pub fn new(player_count: usize) -> MatchState {
+        MatchState {
+            round: 1,
+            wins: vec![0; player_count],
+        }
+    }
+
+    pub fn record_win(&mut self, handle: usize) {
+        if let Some(wins) = self.wins.get_mut(handle) {
+            *wins += 1;
+        }
+    }
+
+    /// The player who won more than half of the rounds
+    pub fn winner(&self, best_of: u16) -> Option<usize> {
+        self.wins
+            .iter()
+            .position(|wins| *wins as u32 * 2 > best_of as u32)
+    }
+
+    /// The next round, or the first round of a rematch once somebody won the match
+    pub fn next_round(&self, best_of: u16) -> MatchState {
+        match self.winner(best_of) {
+            Some(_) => MatchState::new(self.wins.len()),
+            None => MatchState {
+                round: self.round + 1,
+                wins: self.wins.clone(),
+            },
+        }
+    }
+}
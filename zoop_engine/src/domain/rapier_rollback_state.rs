@@ -1,10 +1,56 @@
 use bevy::prelude::*;
+use ggrs::{Frame, NULL_FRAME};
+
+/// More than the 12 frames GGRS predicts ahead, so that every frame a rollback can go back to is kept
+pub const PHYSICS_STATE_FRAMES: usize = 16;
 
 /// Our physics rollback state container, which will be rolled back and we will
-/// use to restore our physics state.
-#[derive(Default, Reflect, Hash, Resource, PartialEq, Eq)]
+/// use to restore our physics state. It only names the frame whose serialized
+/// state to restore from `PhysicsStateBuffers`, so that GGRS doesn't copy the bytes.
+#[derive(Default, Clone, Copy, Reflect, Hash, Resource, PartialEq, Eq)]
 #[reflect(Hash, Resource, PartialEq)]
 pub struct RapierRollbackState {
-    pub rapier_state: Option<Vec<u8>>,
-    pub rapier_checksum: u16,
+    pub frame: Frame,
+    pub rapier_checksum: u64,
+}
+
+/// Serialized physics state of recent frames, should not be rolled back.
+/// A frame is saved into the buffer of the frame `PHYSICS_STATE_FRAMES` before it,
+/// so that buffers are reused instead of allocated every frame.
+#[derive(Resource)]
+pub struct PhysicsStateBuffers(Vec<(Frame, Vec<u8>)>);
+
+impl Default for PhysicsStateBuffers {
+    fn default() -> Self {
+        PhysicsStateBuffers(vec![(NULL_FRAME, vec![]); PHYSICS_STATE_FRAMES])
+    }
+}
+
+impl PhysicsStateBuffers {
+    fn index(frame: Frame) -> usize {
+        frame.rem_euclid(PHYSICS_STATE_FRAMES as Frame) as usize
+    }
+
+    pub fn get(&self, frame: Frame) -> Option<&[u8]> {
+        let (saved_frame, bytes) = &self.0[PhysicsStateBuffers::index(frame)];
+        (*saved_frame == frame && !bytes.is_empty()).then_some(bytes.as_slice())
+    }
+
+    /// The buffer to save a frame into, still holding an older frame
+    pub fn buffer_mut(&mut self, frame: Frame) -> &mut Vec<u8> {
+        let (saved_frame, bytes) = &mut self.0[PhysicsStateBuffers::index(frame)];
+        *saved_frame = frame;
+        bytes
+    }
+
+    pub fn insert(&mut self, frame: Frame, bytes: &[u8]) {
+        let buffer = self.buffer_mut(frame);
+        buffer.clear();
+        buffer.extend_from_slice(bytes);
+    }
 }
+
+/// Physics state saved on the first frame, once the scene's bodies exist and before anything
+/// moved them. Restarting a round restores it, should not be rolled back.
+#[derive(Resource, Default)]
+pub struct RoundStartPhysics(pub Vec<u8>);
@@ -0,0 +1,31 @@
+//! Serde definitions for Rapier components which are only `Reflect`
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ExternalForce")]
+pub struct ExternalForceDef {
+    pub force: Vec2,
+    pub torque: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ExternalImpulse")]
+pub struct ExternalImpulseDef {
+    pub impulse: Vec2,
+    pub torque_impulse: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "MassProperties")]
+pub struct MassPropertiesDef {
+    pub local_center_of_mass: Vec2,
+    pub mass: f32,
+    pub principal_inertia: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ReadMassProperties")]
+pub struct ReadMassPropertiesDef(#[serde(with = "MassPropertiesDef")] pub MassProperties);
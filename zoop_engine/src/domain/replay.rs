@@ -0,0 +1,232 @@
+use crate::domain::frame_inputs::FrameInputs;
+use crate::domain::game_config::GameConfig;
+use crate::domain::input_stream::{InputStream, RecordedControls};
+use crate::domain::track::TrackDefinition;
+use bevy::prelude::*;
+use ggrs::Frame;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use zoop_shared::car_class::CarClass;
+use zoop_shared::network_player::NetworkPlayer;
+use zoop_shared::player_id::PlayerId;
+
+/// Bumped whenever a replay recorded by an older build would simulate differently
+pub const REPLAY_VERSION: u32 = 3;
+
+/// Every `GameConfig` value that affects the simulation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReplayPhysics {
+    pub fps: u16,
+    pub load_seconds: u16,
+    pub countdown_seconds: u16,
+    pub false_start_penalty_seconds: u16,
+    pub race_finish_grace_seconds: u16,
+    pub best_of: u16,
+    pub pixels_per_meter: f32,
+    pub car_half_width: f32,
+    pub car_half_length: f32,
+    pub tire_half_thickness: f32,
+    pub tire_radius: f32,
+    pub tire_rotation_per_tick: f32,
+    pub tire_max_angle: f32,
+    pub tire_acceleration_force: f32,
+    pub tire_reversing_force: f32,
+    pub tire_breaking_force: f32,
+    pub tire_friction_force: f32,
+    pub tire_half_grip_slip_angle: f32,
+    pub tire_handbrake_grip: f32,
+    pub tire_linear_damping: f32,
+    pub tire_angular_damping: f32,
+    pub drive_front_share: Option<f32>,
+    pub rear_steering_ratio: Option<f32>,
+    pub collision_force_threshold: f32,
+    pub collision_damage_per_impulse: f32,
+    pub damage_max_power_loss: f32,
+    pub damage_max_steering_bend: f32,
+    pub car_classes: Vec<CarClass>,
+}
+
+impl ReplayPhysics {
+    pub fn of(config: &GameConfig) -> ReplayPhysics {
+        ReplayPhysics {
+            fps: config.fps,
+            load_seconds: config.load_seconds,
+            countdown_seconds: config.countdown_seconds,
+            false_start_penalty_seconds: config.false_start_penalty_seconds,
+            race_finish_grace_seconds: config.race_finish_grace_seconds,
+            best_of: config.best_of,
+            pixels_per_meter: config.pixels_per_meter,
+            car_half_width: config.car_half_width,
+            car_half_length: config.car_half_length,
+            tire_half_thickness: config.tire_half_thickness,
+            tire_radius: config.tire_radius,
+            tire_rotation_per_tick: config.tire_rotation_per_tick,
+            tire_max_angle: config.tire_max_angle,
+            tire_acceleration_force: config.tire_acceleration_force,
+            tire_reversing_force: config.tire_reversing_force,
+            tire_breaking_force: config.tire_breaking_force,
+            tire_friction_force: config.tire_friction_force,
+            tire_half_grip_slip_angle: config.tire_half_grip_slip_angle,
+            tire_handbrake_grip: config.tire_handbrake_grip,
+            tire_linear_damping: config.tire_linear_damping,
+            tire_angular_damping: config.tire_angular_damping,
+            drive_front_share: config.drive_front_share,
+            rear_steering_ratio: config.rear_steering_ratio,
+            collision_force_threshold: config.collision_force_threshold,
+            collision_damage_per_impulse: config.collision_damage_per_impulse,
+            damage_max_power_loss: config.damage_max_power_loss,
+            damage_max_steering_bend: config.damage_max_steering_bend,
+            car_classes: config.car_classes.clone(),
+        }
+    }
+
+    pub fn apply(&self, config: &mut GameConfig) {
+        config.fps = self.fps;
+        config.load_seconds = self.load_seconds;
+        config.countdown_seconds = self.countdown_seconds;
+        config.false_start_penalty_seconds = self.false_start_penalty_seconds;
+        config.race_finish_grace_seconds = self.race_finish_grace_seconds;
+        config.best_of = self.best_of;
+        config.pixels_per_meter = self.pixels_per_meter;
+        config.car_half_width = self.car_half_width;
+        config.car_half_length = self.car_half_length;
+        config.tire_half_thickness = self.tire_half_thickness;
+        config.tire_radius = self.tire_radius;
+        config.tire_rotation_per_tick = self.tire_rotation_per_tick;
+        config.tire_max_angle = self.tire_max_angle;
+        config.tire_acceleration_force = self.tire_acceleration_force;
+        config.tire_reversing_force = self.tire_reversing_force;
+        config.tire_breaking_force = self.tire_breaking_force;
+        config.tire_friction_force = self.tire_friction_force;
+        config.tire_half_grip_slip_angle = self.tire_half_grip_slip_angle;
+        config.tire_handbrake_grip = self.tire_handbrake_grip;
+        config.tire_linear_damping = self.tire_linear_damping;
+        config.tire_angular_damping = self.tire_angular_damping;
+        config.drive_front_share = self.drive_front_share;
+        config.rear_steering_ratio = self.rear_steering_ratio;
+        config.collision_force_threshold = self.collision_force_threshold;
+        config.collision_damage_per_impulse = self.collision_damage_per_impulse;
+        config.damage_max_power_loss = self.damage_max_power_loss;
+        config.damage_max_steering_bend = self.damage_max_steering_bend;
+        config.car_classes = self.car_classes.clone();
+    }
+}
+
+/// Everything needed to simulate a race again, frame by frame
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub version: u32,
+    /// In player handle order
+    pub players: Vec<PlayerId>,
+    pub physics: ReplayPhysics,
+    pub track: TrackDefinition,
+    pub inputs: InputStream,
+}
+
+/// Read first, so that replays of other versions fail with a clear error
+#[derive(Deserialize)]
+struct ReplayHeader {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(String),
+    Parse(String),
+    Version(u32),
+}
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "Failed to read replay: {}", e),
+            ReplayError::Parse(e) => write!(f, "Failed to parse replay: {}", e),
+            ReplayError::Version(version) => write!(
+                f,
+                "Replay version {} can't be played, this build plays version {}",
+                version, REPLAY_VERSION
+            ),
+        }
+    }
+}
+
+impl Replay {
+    pub fn record(config: &GameConfig, inputs: InputStream) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            players: config.players.iter().map(|p| p.id.clone()).collect(),
+            physics: ReplayPhysics::of(config),
+            track: config.track.clone(),
+            inputs,
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Replay, ReplayError> {
+        let header: ReplayHeader =
+            serde_json::from_str(json).map_err(|e| ReplayError::Parse(e.to_string()))?;
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::Version(header.version));
+        }
+        serde_json::from_str(json).map_err(|e| ReplayError::Parse(e.to_string()))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Replay, ReplayError> {
+        let json = std::fs::read_to_string(path).map_err(|e| ReplayError::Io(e.to_string()))?;
+        Replay::from_json(&json)
+    }
+
+    pub fn to_file(&self, path: &Path) -> Result<(), ReplayError> {
+        let json = serde_json::to_string(self).map_err(|e| ReplayError::Parse(e.to_string()))?;
+        std::fs::write(path, json).map_err(|e| ReplayError::Io(e.to_string()))
+    }
+
+    /// Nobody is local while watching a replay
+    pub fn network_players(&self) -> Vec<NetworkPlayer> {
+        self.players
+            .iter()
+            .map(|id| NetworkPlayer {
+                id: id.clone(),
+                is_local: false,
+                is_spectator: false,
+            })
+            .collect()
+    }
+}
+
+/// Inputs of every simulated frame, resimulated frames overwrite their predictions
+#[derive(Resource, Clone, Debug, Default)]
+pub struct InputRecorder(pub InputStream);
+
+impl InputRecorder {
+    pub fn new(player_count: usize) -> InputRecorder {
+        InputRecorder(InputStream {
+            player_count,
+            frames: vec![],
+        })
+    }
+
+    pub fn record(&mut self, frame: Frame, inputs: &FrameInputs) {
+        let Ok(index) = usize::try_from(frame - 1) else {
+            return;
+        };
+        // A rollback drops every frame after the resimulated one
+        self.0.frames.truncate(index);
+        self.0.frames.resize(index, vec![]);
+        self.0.frames.push(
+            inputs
+                .0
+                .iter()
+                .map(|(controls, _)| RecordedControls::from(*controls))
+                .collect(),
+        );
+    }
+
+    /// Inputs up to a frame which won't be rolled back anymore
+    pub fn confirmed(&self, confirmed_frame: Frame) -> InputStream {
+        let frames = usize::try_from(confirmed_frame).unwrap_or(0);
+        InputStream {
+            player_count: self.0.player_count,
+            frames: self.0.frames.iter().take(frames).cloned().collect(),
+        }
+    }
+}
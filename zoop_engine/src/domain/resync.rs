@@ -0,0 +1,131 @@
+use crate::domain::game_state::GameState;
+use crate::domain::tire::TirePhysics;
+use crate::services::websocket::NonBlockingWebSocket;
+use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use ggrs::Frame;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Room servers reject WebSocket frames over 64 KiB, snapshots are sent in parts
+pub const RESYNC_CHUNK_SIZE: usize = 32 * 1024;
+
+/// Recovery protocol after a desync, the player with the lowest handle is the authority
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ResyncMessage {
+    /// Somebody noticed a desync, everyone stops simulating
+    Request { frame: Frame },
+    /// Part of the authority's serialized `ResyncSnapshot`
+    SnapshotChunk {
+        frame: Frame,
+        index: usize,
+        count: usize,
+        data: String,
+    },
+    /// The snapshot was loaded
+    Loaded { frame: Frame },
+    /// Everyone loaded the snapshot, start a new session from it
+    Restart { frame: Frame },
+}
+
+/// Rolled back state of a rollback entity, `Entity` ids are equal on every peer
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ResyncBody {
+    pub entity: u64,
+    pub transform: Transform,
+    pub velocity: Option<Velocity>,
+    pub tire: Option<TirePhysics>,
+}
+
+/// Everything that is rolled back, for one frame
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ResyncSnapshot {
+    pub frame: Frame,
+    pub rapier_checksum: u64,
+    pub rapier_state: Vec<u8>,
+    pub game_state: GameState,
+    pub enable_physics_after: EnablePhysicsAfter,
+    pub bodies: Vec<ResyncBody>,
+}
+
+/// Snapshots of recent frames, so that the authority can send a confirmed one.
+/// Should not be rolled back, resimulated frames replace their snapshots.
+#[derive(Resource, Default)]
+pub struct ResyncHistory(pub VecDeque<ResyncSnapshot>);
+
+impl ResyncHistory {
+    pub fn push(&mut self, snapshot: ResyncSnapshot, max_len: usize) {
+        while self
+            .0
+            .back()
+            .map(|last| last.frame >= snapshot.frame)
+            .unwrap_or(false)
+        {
+            self.0.pop_back();
+        }
+        self.0.push_back(snapshot);
+        while self.0.len() > max_len {
+            self.0.pop_front();
+        }
+    }
+
+    /// The latest snapshot which can't be rolled back anymore, or the oldest one we have
+    pub fn confirmed(&self, confirmed_frame: Frame) -> Option<&ResyncSnapshot> {
+        self.0
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.frame <= confirmed_frame)
+            .or_else(|| self.0.front())
+    }
+}
+
+/// The room connection, shared with the GGRS session so that it outlives it
+#[derive(Resource, Clone)]
+pub struct RoomSocket(pub NonBlockingWebSocket);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResyncPhase {
+    Idle,
+    /// Non-authorities wait for every part of the snapshot
+    AwaitingSnapshot {
+        chunks: HashMap<usize, String>,
+    },
+    /// The authority waits for everyone to load its snapshot
+    AwaitingLoaded {
+        frame: Frame,
+        loaded: HashSet<usize>,
+    },
+    /// Non-authorities wait for everyone else to load the snapshot
+    AwaitingRestart {
+        frame: Frame,
+    },
+}
+
+/// Progress of the recovery, should not be rolled back
+#[derive(Resource, Clone, Debug)]
+pub struct Resync {
+    pub phase: ResyncPhase,
+    /// Seconds since the recovery started
+    pub elapsed: f32,
+    pub completed: u16,
+    /// The match can't be recovered anymore
+    pub gave_up: bool,
+}
+
+impl Default for Resync {
+    fn default() -> Self {
+        Resync {
+            phase: ResyncPhase::Idle,
+            elapsed: 0.0,
+            completed: 0,
+            gave_up: false,
+        }
+    }
+}
+
+impl Resync {
+    pub fn is_active(&self) -> bool {
+        self.phase != ResyncPhase::Idle
+    }
+}
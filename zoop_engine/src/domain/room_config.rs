@@ -1,4 +1,4 @@
-use url::Url;
+use url::{ParseError, Url};
 use zoop_shared::player_id::PlayerId;
 use zoop_shared::room_id::RoomId;
 
@@ -9,3 +9,17 @@ pub struct RoomConfig {
     pub user_id: PlayerId,
     pub user_ticket: String,
 }
+
+impl RoomConfig {
+    pub fn game_room_address(&self) -> Result<Url, ParseError> {
+        self.server_address.join(
+            format!(
+                "/api/game/connect/{}/as/{}/ticket/{}",
+                self.room.0.to_string(),
+                self.user_id.0.to_string(),
+                self.user_ticket
+            )
+            .as_str(),
+        )
+    }
+}
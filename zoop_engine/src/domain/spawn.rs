@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use std::fmt;
 
 /// A marker component for spawning first thing when the app launches.  This
 /// just contains some arbitrary data, it actually isn't critical (it's used to
@@ -22,3 +23,51 @@ impl DeterministicSpawnBundle {
         }
     }
 }
+
+/// Ran out of pre-spawned entities, spawning more would give different entities on every peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpawnError {
+    Exhausted { allocated: usize },
+}
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpawnError::Exhausted { allocated } => write!(
+                f,
+                "The scene needs more than the {} pre-spawned entities",
+                allocated
+            ),
+        }
+    }
+}
+
+/// Pre-spawned entities, handed out in spawn index order so that every peer gets the same ones
+pub struct SpawnPool {
+    /// Reversed for easy popping
+    entities: Vec<Entity>,
+    allocated: usize,
+}
+
+impl SpawnPool {
+    pub fn new<'a>(spawns: impl Iterator<Item = (Entity, &'a DeterministicSpawn)>) -> SpawnPool {
+        let mut spawns: Vec<(Entity, &DeterministicSpawn)> = spawns.collect();
+        spawns.sort_by_key(|(_, spawn)| std::cmp::Reverse(spawn.index));
+        let entities: Vec<Entity> = spawns.into_iter().map(|(entity, _)| entity).collect();
+
+        SpawnPool {
+            allocated: entities.len(),
+            entities,
+        }
+    }
+
+    pub fn pop(&mut self) -> Result<Entity, SpawnError> {
+        self.entities.pop().ok_or(SpawnError::Exhausted {
+            allocated: self.allocated,
+        })
+    }
+
+    /// Entities which weren't needed for the scene
+    pub fn leftovers(&mut self) -> Vec<Entity> {
+        std::mem::take(&mut self.entities)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::domain::colors::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Material under a tire, everything outside of a tagged region is asphalt
+#[derive(
+    Serialize, Deserialize, Reflect, FromReflect, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SurfaceKind {
+    #[default]
+    Asphalt,
+    Grass,
+    Ice,
+    Mud,
+}
+
+/// How a surface changes the handling of a tire rolling over it
+pub struct SurfaceTraits {
+    /// Multiplies the tire friction
+    pub grip: f32,
+    /// Share of the rolling velocity lost every tick
+    pub rolling_resistance: f32,
+    /// Tires stop pushing above this speed, in meters per second
+    pub max_speed: Option<f32>,
+}
+
+pub fn surface_traits(kind: SurfaceKind) -> SurfaceTraits {
+    match kind {
+        SurfaceKind::Asphalt => SurfaceTraits {
+            grip: 1.0,
+            rolling_resistance: 0.0,
+            max_speed: None,
+        },
+        // Slows down anybody cutting corners
+        SurfaceKind::Grass => SurfaceTraits {
+            grip: 0.6,
+            rolling_resistance: 0.02,
+            max_speed: Some(20.0),
+        },
+        // Keeps its speed, but barely turns
+        SurfaceKind::Ice => SurfaceTraits {
+            grip: 0.15,
+            rolling_resistance: 0.0,
+            max_speed: None,
+        },
+        SurfaceKind::Mud => SurfaceTraits {
+            grip: 0.8,
+            rolling_resistance: 0.05,
+            max_speed: Some(10.0),
+        },
+    }
+}
+
+pub fn surface_color(kind: SurfaceKind) -> Color {
+    match kind {
+        SurfaceKind::Asphalt => ZOOP_GREY,
+        SurfaceKind::Grass => ZOOP_GREEN,
+        SurfaceKind::Ice => ZOOP_LIGHT_BLUE,
+        SurfaceKind::Mud => ZOOP_BROWN,
+    }
+}
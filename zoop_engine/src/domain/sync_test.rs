@@ -0,0 +1,138 @@
+use crate::domain::controls::Controls;
+use bevy::prelude::*;
+use ggrs::Frame;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Pseudo-random inputs are held for this many frames, so that cars actually get somewhere
+const RANDOM_INPUT_HOLD_FRAMES: Frame = 15;
+
+/// Controls which a player holds from `frame` until the next input of the same player
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScriptedInput {
+    pub frame: Frame,
+    pub handle: usize,
+    #[serde(default)]
+    pub steering: i8,
+    #[serde(default)]
+    pub throttle: u8,
+    #[serde(default)]
+    pub reverse: u8,
+    #[serde(default)]
+    pub brake: u8,
+    #[serde(default)]
+    pub handbrake: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum SyncTestInputs {
+    /// Player 0 is driven from local input devices, the rest idle
+    Local,
+    /// Reproducible pseudo-random inputs for every player
+    Random {
+        seed: u64,
+    },
+    Scripted(Vec<ScriptedInput>),
+}
+
+#[derive(Clone, Debug)]
+pub struct SyncTestConfig {
+    pub player_count: usize,
+    /// How many frames back every frame is resimulated
+    pub check_distance: usize,
+    pub inputs: SyncTestInputs,
+    /// Stop after this many frames, keep running otherwise
+    pub frames: Option<Frame>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyncTestResult {
+    Passed {
+        frames: Frame,
+    },
+    Mismatch {
+        frame: Frame,
+        checksum: u64,
+        resimulated_checksum: u64,
+    },
+}
+impl fmt::Display for SyncTestResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SyncTestResult::Passed { frames } => {
+                write!(f, "Sync test passed after {} frames", frames)
+            }
+            SyncTestResult::Mismatch {
+                frame,
+                checksum,
+                resimulated_checksum,
+            } => write!(
+                f,
+                "Sync test failed on frame {}, Rapier checksum {} resimulated as {}",
+                frame, checksum, resimulated_checksum
+            ),
+        }
+    }
+}
+
+/// Rapier checksums of frames which may still be resimulated, not rolled back
+#[derive(Resource, Default, Debug)]
+pub struct SyncTestChecksums(pub HashMap<Frame, u64>);
+
+/// SplitMix64, good enough for test inputs and identical on every platform
+fn mix(seed: u64) -> u64 {
+    let mut x = seed.wrapping_add(0x9E3779B97F4A7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+    x ^ (x >> 31)
+}
+
+impl SyncTestInputs {
+    /// Generated controls of a player for a frame, `None` if local input devices should be read
+    pub fn controls(
+        &self,
+        handle: usize,
+        frame: Frame,
+        last_confirmed_hash: u64,
+        last_confirmed_frame: Frame,
+    ) -> Option<Controls> {
+        let mut controls = Controls::empty(last_confirmed_hash, last_confirmed_frame);
+        match self {
+            SyncTestInputs::Local => {
+                if handle == 0 {
+                    return None;
+                }
+            }
+            SyncTestInputs::Random { seed } => {
+                let period = (frame.max(0) / RANDOM_INPUT_HOLD_FRAMES) as u64;
+                let bits = mix(seed ^ mix(period ^ ((handle as u64) << 32)));
+                controls.steering = (bits as u8 as i8).max(-i8::MAX);
+                controls.throttle = (bits >> 8) as u8;
+                // Reversing and braking only every now and then
+                if (bits >> 16) & 0b111 == 0 {
+                    controls.reverse = (bits >> 24) as u8;
+                }
+                if (bits >> 19) & 0b111 == 0 {
+                    controls.brake = (bits >> 32) as u8;
+                }
+                controls.set_handbrake((bits >> 22) & 0b111 == 0);
+            }
+            SyncTestInputs::Scripted(script) => {
+                let held = script
+                    .iter()
+                    .filter(|input| input.handle == handle && input.frame <= frame)
+                    .max_by_key(|input| input.frame);
+                if let Some(input) = held {
+                    controls.steering = input.steering.max(-i8::MAX);
+                    controls.throttle = input.throttle;
+                    controls.reverse = input.reverse;
+                    controls.brake = input.brake;
+                    controls.set_handbrake(input.handbrake);
+                }
+            }
+        }
+
+        Some(controls)
+    }
+}
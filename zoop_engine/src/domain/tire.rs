@@ -4,6 +4,7 @@ use bevy::core::Name;
 use bevy::math::*;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, Debug, Default, Component, Reflect, FromReflect)]
 #[reflect(Component)]
@@ -12,7 +13,7 @@ pub struct TireMeta {
     pub is_right: bool,
 }
 
-#[derive(Copy, Clone, Debug, Default, Component, Reflect, FromReflect)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, Component, Reflect, FromReflect)]
 #[reflect(Component)]
 pub struct TirePhysics {
     pub angle: f32,
@@ -37,6 +38,7 @@ pub struct Tire {
     restitution: Restitution,
     friction: Friction,
     active_events: ActiveEvents,
+    contact_force_threshold: ContactForceEventThreshold,
     ccd: Ccd,
     collision_groups: CollisionGroups,
     sprite_bundle: SpriteBundle,
@@ -59,6 +61,7 @@ impl Tire {
         car_title: String,
         color: Color,
         damping: Damping,
+        contact_force_threshold: f32,
         physics: GameTire,
     ) -> Tire {
         let front_title = if is_front { "F" } else { "B" };
@@ -84,7 +87,9 @@ impl Tire {
             locked_axes: LockedAxes::default(),
             restitution: Restitution::default(),
             friction: Friction::default(),
-            active_events: ActiveEvents::empty(),
+            // Tires stick out of the body, so they are hit first from the side
+            active_events: ActiveEvents::CONTACT_FORCE_EVENTS,
+            contact_force_threshold: ContactForceEventThreshold(contact_force_threshold),
             ccd: Ccd::disabled(),
             collision_groups: CollisionGroups::default(),
             sprite_bundle: SpriteBundle {
@@ -0,0 +1,157 @@
+use crate::domain::surface::SurfaceKind;
+use crate::logic::math::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+const EMBEDDED_OVAL: &str = include_str!("../../assets/tracks/oval.json");
+
+/// A rotated rectangle on the track, all units in meters and degrees
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrackRect {
+    pub x: f32,
+    pub y: f32,
+    pub half_width: f32,
+    pub half_height: f32,
+    #[serde(default)]
+    pub angle: f32,
+}
+impl TrackRect {
+    pub fn transform(&self, pixels_per_meter: f32, z: f32) -> Transform {
+        Transform::from_xyz(self.x * pixels_per_meter, self.y * pixels_per_meter, z)
+            .with_rotation(Quat::from_rotation_z(deg2rad(self.angle)))
+    }
+
+    pub fn half_size(&self, pixels_per_meter: f32) -> Vec2 {
+        Vec2::new(self.half_width, self.half_height) * pixels_per_meter
+    }
+
+    /// Whether a point in meters is inside, edges included
+    pub fn contains(&self, point: Vec2) -> bool {
+        let local =
+            Vec2::from_angle(-deg2rad(self.angle)).rotate(point - Vec2::new(self.x, self.y));
+        local.x.abs() <= self.half_width && local.y.abs() <= self.half_height
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Reflect, FromReflect, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackWallKind {
+    #[default]
+    Wall,
+    Barrier,
+}
+
+/// Static collider which cars bounce off
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TrackWall {
+    #[serde(default)]
+    pub kind: TrackWallKind,
+    #[serde(flatten)]
+    pub rect: TrackRect,
+}
+
+/// Region with a different surface than asphalt, purely a lookup without colliders
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TrackSurface {
+    pub kind: SurfaceKind,
+    #[serde(flatten)]
+    pub rect: TrackRect,
+}
+
+/// Starting grid, cars are placed in rows behind the start position
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TrackStart {
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32,
+    pub columns: usize,
+    pub column_spacing: f32,
+    pub row_spacing: f32,
+}
+impl TrackStart {
+    /// Grid position in meters and car rotation in radians for a player handle
+    pub fn grid_slot(&self, handle: usize) -> (Vec2, f32) {
+        let columns = self.columns.max(1);
+        let row = (handle / columns) as f32;
+        let column = (handle % columns) as f32 - (columns - 1) as f32 / 2.0;
+        let angle = deg2rad(self.angle);
+        let forward = Vec2::from_angle(angle).rotate(Vec2::Y);
+        let right = Vec2::from_angle(angle).rotate(Vec2::X);
+        let position = Vec2::new(self.x, self.y) - forward * row * self.row_spacing
+            + right * column * self.column_spacing;
+
+        (position, angle)
+    }
+}
+
+fn default_laps() -> u16 {
+    3
+}
+
+/// Race track layout, loaded from JSON
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TrackDefinition {
+    pub name: String,
+    /// Laps needed to finish the race
+    #[serde(default = "default_laps")]
+    pub laps: u16,
+    pub start: TrackStart,
+    /// Crossing this after all checkpoints completes a lap
+    pub finish: TrackRect,
+    /// Sensors which have to be crossed in order during each lap
+    #[serde(default)]
+    pub checkpoints: Vec<TrackRect>,
+    /// Drivable area, purely visual
+    #[serde(default)]
+    pub areas: Vec<TrackRect>,
+    #[serde(default)]
+    pub walls: Vec<TrackWall>,
+    /// Surfaces drawn over the areas, later ones cover earlier ones
+    #[serde(default)]
+    pub surfaces: Vec<TrackSurface>,
+}
+
+#[derive(Debug)]
+pub enum TrackError {
+    Io(String),
+    Parse(String),
+}
+impl fmt::Display for TrackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrackError::Io(e) => write!(f, "Failed to read track file: {}", e),
+            TrackError::Parse(e) => write!(f, "Failed to parse track definition: {}", e),
+        }
+    }
+}
+
+impl TrackDefinition {
+    pub fn from_json(json: &str) -> Result<TrackDefinition, TrackError> {
+        serde_json::from_str(json).map_err(|e| TrackError::Parse(e.to_string()))
+    }
+
+    pub fn from_file(path: &Path) -> Result<TrackDefinition, TrackError> {
+        let json = std::fs::read_to_string(path).map_err(|e| TrackError::Io(e.to_string()))?;
+        TrackDefinition::from_json(&json)
+    }
+
+    /// Surface at a point in meters, the last region in file order wins where they overlap
+    pub fn surface_at(&self, point: Vec2) -> SurfaceKind {
+        self.surfaces
+            .iter()
+            .rev()
+            .find(|surface| surface.rect.contains(point))
+            .map(|surface| surface.kind)
+            .unwrap_or_default()
+    }
+}
+
+impl Default for TrackDefinition {
+    fn default() -> Self {
+        TrackDefinition::from_json(EMBEDDED_OVAL).expect("Embedded track must be valid")
+    }
+}
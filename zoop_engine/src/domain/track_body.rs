@@ -0,0 +1,141 @@
+use crate::domain::game_state::{GameCheckpoint, GameGround, GameWall};
+use crate::domain::track::TrackWallKind;
+use bevy::core::Name;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+#[derive(Component)]
+pub struct WallMeta {
+    pub kind: TrackWallKind,
+    pub half_size: Vec2,
+}
+
+#[derive(Component)]
+pub struct GroundMeta {
+    pub half_size: Vec2,
+}
+
+#[derive(Component)]
+pub struct CheckpointMeta {
+    pub index: u16,
+    pub is_finish: bool,
+}
+
+#[derive(Bundle)]
+pub struct WallBody {
+    meta: WallMeta,
+    name: Name,
+    rigid_body: RigidBody,
+    collider: Collider,
+    collider_scale: ColliderScale,
+    restitution: Restitution,
+    friction: Friction,
+    active_events: ActiveEvents,
+    contact_force_threshold: ContactForceEventThreshold,
+    collision_groups: CollisionGroups,
+    sprite: SpriteBundle,
+}
+
+impl WallBody {
+    pub fn build(
+        title: String,
+        color: Color,
+        contact_force_threshold: f32,
+        wall: GameWall,
+    ) -> WallBody {
+        WallBody {
+            meta: WallMeta {
+                kind: wall.kind,
+                half_size: wall.half_size,
+            },
+            name: Name::new(title),
+            rigid_body: RigidBody::Fixed,
+            collider: Collider::cuboid(wall.half_size.x, wall.half_size.y),
+            collider_scale: ColliderScale::Absolute(Vec2::new(1., 1.)),
+            restitution: Restitution::default(),
+            friction: Friction::default(),
+            active_events: ActiveEvents::CONTACT_FORCE_EVENTS,
+            contact_force_threshold: ContactForceEventThreshold(contact_force_threshold),
+            collision_groups: CollisionGroups::default(),
+            sprite: SpriteBundle {
+                transform: wall.transform,
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(wall.half_size * 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+        }
+    }
+}
+
+/// Drivable surface, only drawn and never touched by physics
+#[derive(Bundle)]
+pub struct GroundBody {
+    meta: GroundMeta,
+    name: Name,
+    sprite: SpriteBundle,
+}
+
+impl GroundBody {
+    pub fn build(title: String, color: Color, ground: GameGround) -> GroundBody {
+        GroundBody {
+            meta: GroundMeta {
+                half_size: ground.half_size,
+            },
+            name: Name::new(title),
+            sprite: SpriteBundle {
+                transform: ground.transform,
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(ground.half_size * 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+        }
+    }
+}
+
+/// Sensor which tracks lap progress, only the finish line is visible
+#[derive(Bundle)]
+pub struct CheckpointBody {
+    meta: CheckpointMeta,
+    name: Name,
+    rigid_body: RigidBody,
+    collider: Collider,
+    sensor: Sensor,
+    active_events: ActiveEvents,
+    sprite: SpriteBundle,
+}
+
+impl CheckpointBody {
+    pub fn build(title: String, color: Color, checkpoint: GameCheckpoint) -> CheckpointBody {
+        CheckpointBody {
+            meta: CheckpointMeta {
+                index: checkpoint.index,
+                is_finish: checkpoint.is_finish,
+            },
+            name: Name::new(title),
+            rigid_body: RigidBody::Fixed,
+            collider: Collider::cuboid(checkpoint.half_size.x, checkpoint.half_size.y),
+            sensor: Sensor,
+            active_events: ActiveEvents::empty(),
+            sprite: SpriteBundle {
+                transform: checkpoint.transform,
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(checkpoint.half_size * 2.0),
+                    ..default()
+                },
+                visibility: if checkpoint.is_finish {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                },
+                ..default()
+            },
+        }
+    }
+}
@@ -5,4 +5,10 @@ mod logic;
 mod services;
 mod systems;
 
+pub use domain::input_stream::{HeadlessResult, InputStream, RecordedControls};
+pub use domain::key_bindings::KeyBindings;
+pub use domain::physics_snapshot::PhysicsSnapshot;
+pub use domain::replay::{Replay, ReplayError};
+pub use domain::sync_test::{ScriptedInput, SyncTestConfig, SyncTestInputs, SyncTestResult};
+pub use domain::track::TrackDefinition;
 pub use services::interop::*;
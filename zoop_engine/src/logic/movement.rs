@@ -10,34 +10,21 @@ pub fn tire_angle_change(
     controls: &Controls,
     max_steering_angle: f32,
     rotation_step: f32,
+    rear_steering_ratio: f32,
+    steering_bend: f32,
 ) -> f32 {
-    if !tire_meta.is_front {
-        // Back wheels don't steer
-        0.0
+    // Steer towards the angle proportional to the input,
+    // which is the center when not steering at all
+    // (positive angles steer left, positive input steers right)
+    let front_angle = -controls.steering_amount() * max_steering_angle;
+    let target_angle = if tire_meta.is_front {
+        // A damaged car pulls to one side even when not steering
+        front_angle + steering_bend
     } else {
-        if controls.steering_left() && tire_physics.angle < max_steering_angle {
-            // Steering left
-            rotation_step
-        } else if controls.steering_right() && tire_physics.angle > -max_steering_angle {
-            // Steering right
-            -rotation_step
-        } else if !controls.steering_any() {
-            // Steering back to center
-            if tire_physics.angle < rotation_step && tire_physics.angle > -rotation_step {
-                // Already at center
-                0.0
-            } else if tire_physics.angle <= -rotation_step {
-                // Steering from left
-                rotation_step
-            } else {
-                // Steering from right
-                -rotation_step
-            }
-        } else {
-            // Steering over treshold - keeping same angle
-            0.0
-        }
-    }
+        // Back wheels don't steer unless the car has rear-wheel steering
+        -front_angle * rear_steering_ratio
+    };
+    (target_angle - tire_physics.angle).clamp(-rotation_step, rotation_step)
 }
 
 pub fn tire_acceleration(
@@ -48,29 +35,41 @@ pub fn tire_acceleration(
     acceleration_force: f32,
     reversing_force: f32,
     breaking_force: f32,
+    drive_share: f32,
 ) -> f32 {
-    if !tire_meta.is_front {
-        // Only front wheels accelerate currently
-        0.0
-    } else {
-        if controls.breaking() && direction_velocity.abs() > pixels_per_meter * 0.05 {
-            // Breaking deceleration
-            signed(direction_velocity < 0.0, breaking_force)
-        } else if controls.reversing() {
-            // Backwards acceleration
-            -reversing_force
-        } else if controls.accelerating() {
-            // Forwards acceleration
-            acceleration_force
+    if controls.breaking() && direction_velocity.abs() > pixels_per_meter * 0.05 {
+        // Breaking deceleration, only front wheels brake
+        if tire_meta.is_front {
+            signed(
+                direction_velocity < 0.0,
+                breaking_force * controls.brake_amount(),
+            )
         } else {
-            // No acceleration
             0.0
         }
+    } else if controls.reversing() {
+        // Backwards acceleration
+        -reversing_force * controls.reverse_amount() * drive_share
+    } else if controls.accelerating() {
+        // Forwards acceleration
+        acceleration_force * controls.throttle_amount() * drive_share
+    } else {
+        // No acceleration
+        0.0
     }
 }
 
+/// Share of the grip a tire keeps at a slip angle, a saturating curve: full grip when rolling
+/// straight, half of it at `half_grip_slip_angle` and less and less beyond, which lets cars drift.
+/// Plain arithmetic instead of `tanh` or `atan`, so that every platform computes the same value.
+pub fn grip_curve(slip_angle: f32, half_grip_slip_angle: f32) -> f32 {
+    1.0 / (1.0 + slip_angle / half_grip_slip_angle)
+}
+
 pub fn tire_friction_impulse(
     tire_friction_force: f32,
+    half_grip_slip_angle: f32,
+    grip: f32,
     tire_direction: &Vec2,
     tire_velocity: &Vec2,
 ) -> Vec2 {
@@ -80,8 +79,15 @@ pub fn tire_friction_impulse(
     } else {
         velocity_angle_unsafe
     };
-    let slide_amount =
-        ComplexField::sin(velocity_angle).abs() * tire_velocity.length() * tire_friction_force;
+    // Rolling backwards doesn't slip, so the angle is measured from the closer rolling direction
+    let slip_angle = velocity_angle
+        .abs()
+        .min(deg2rad(180.0) - velocity_angle.abs());
+    let lateral_velocity = ComplexField::sin(velocity_angle).abs() * tire_velocity.length();
+    let slide_amount = lateral_velocity
+        * tire_friction_force
+        * grip
+        * grip_curve(slip_angle, half_grip_slip_angle);
     let slide_direction = if velocity_angle < 0.0 {
         deg2rad(90.0)
     } else {
@@ -92,3 +98,26 @@ pub fn tire_friction_impulse(
 
     friction_impulse
 }
+
+/// Drops the drive force once the tire rolls faster than the surface allows,
+/// forces against the rolling direction still slow it down
+pub fn surface_limited_force(force: f32, direction_velocity: f32, max_speed: Option<f32>) -> f32 {
+    match max_speed {
+        Some(max_speed)
+            if direction_velocity.abs() > max_speed
+                && force.signum() == direction_velocity.signum() =>
+        {
+            0.0
+        }
+        _ => force,
+    }
+}
+
+/// Impulse against the rolling direction, which soft surfaces drag on
+pub fn rolling_resistance_impulse(
+    rolling_resistance: f32,
+    tire_direction: &Vec2,
+    direction_velocity: f32,
+) -> Vec2 {
+    -*tire_direction * direction_velocity * rolling_resistance
+}
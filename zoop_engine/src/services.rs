@@ -1,2 +1,7 @@
+pub mod desync_report;
 pub mod interop;
+pub mod race_result;
+pub mod replay;
+pub mod sync_test;
+pub mod tuning_handshake;
 pub mod websocket;
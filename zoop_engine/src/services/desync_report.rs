@@ -0,0 +1,29 @@
+use crate::domain::desync_report::DesyncReport;
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+static DESYNC_REPORT: Mutex<Option<DesyncReport>> = Mutex::new(None);
+
+/// Keeps the report for interop and writes it into a directory, if there is a file system
+pub fn publish_desync_report(report: DesyncReport, dir: Option<&Path>) -> Option<PathBuf> {
+    let path = dir.and_then(|dir| {
+        let path = dir.join(report.file_name());
+        let json = serde_json::to_string(&report).ok()?;
+        match std::fs::write(&path, json) {
+            Ok(()) => Some(path),
+            Err(error) => {
+                error!("Failed to write desync report: {}", error);
+                None
+            }
+        }
+    });
+    if let Ok(mut stored) = DESYNC_REPORT.lock() {
+        *stored = Some(report);
+    }
+    path
+}
+
+pub fn desync_report() -> Option<DesyncReport> {
+    DESYNC_REPORT.lock().ok().and_then(|report| report.clone())
+}
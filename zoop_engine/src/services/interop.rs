@@ -1,10 +1,27 @@
 use crate::domain::game_config::GameConfig;
+use crate::domain::game_mode::GameMode;
+use crate::domain::input_stream::{HeadlessResult, InputStream};
+use crate::domain::key_bindings::KeyBindings;
+use crate::domain::rapier_rollback_state::RapierRollbackState;
+use crate::domain::replay::Replay;
 use crate::domain::room_config::RoomConfig;
-use crate::systems::build_game::build_game;
+use crate::domain::sync_test::{SyncTestConfig, SyncTestResult};
+use crate::domain::track::TrackDefinition;
+use crate::services::desync_report::desync_report;
+use crate::services::race_result::race_result;
+use crate::services::replay::replay;
+use crate::services::sync_test::sync_test_result;
+use crate::services::tuning_handshake::tuning_mismatch;
+use crate::systems::build_game::{build_game, build_headless_game};
+use crate::systems::replay_playback::{step_recorded_frame, ReplayPlayback};
 use bevy::prelude::*;
+use bevy_ggrs::GGRSSchedule;
+use ggrs::Frame;
+use std::path::PathBuf;
 use url::Url;
 use uuid::Uuid;
 use wasm_bindgen::prelude::*;
+use zoop_shared::car_tuning::CarTuning;
 use zoop_shared::network_player::NetworkPlayer;
 use zoop_shared::player_id::PlayerId;
 use zoop_shared::room_config::GameRoomConfig;
@@ -19,12 +36,22 @@ pub fn networked_game_raw(
     room_uuid: String,
     room_config_json: String,
     canvas_selector: Option<String>,
+    key_bindings_json: Option<String>,
 ) {
+    // The browser keeps key bindings in localStorage, broken ones fall back to defaults
+    let key_bindings = key_bindings_json.and_then(|json| match KeyBindings::from_json(&json) {
+        Ok(key_bindings) => Some(key_bindings),
+        Err(error) => {
+            println!("{}", error);
+            None
+        }
+    });
     let player_id = PlayerId(Uuid::parse_str(&user_uuid).unwrap());
     let room_config = serde_json::from_str(&room_config_json).unwrap();
     let room_id = RoomId(Uuid::parse_str(&room_uuid).unwrap());
 
-    networked_game(
+    // Browsers return from `run` right away, a tuning mismatch is shown on the HUD
+    let result = networked_game(
         http_baseurl,
         ws_baseurl,
         player_id,
@@ -32,7 +59,40 @@ pub fn networked_game_raw(
         room_id,
         room_config,
         canvas_selector,
-    )
+        None,
+        key_bindings,
+        None,
+        None,
+    );
+    if let Err(error) = result {
+        println!("{}", error);
+    }
+}
+
+/// Final standings as JSON, available once the race is over
+#[wasm_bindgen]
+pub fn race_result_json() -> Option<String> {
+    race_result().and_then(|result| serde_json::to_string(&result).ok())
+}
+
+/// Diagnostics of the desync that ended the match as JSON, if there was one
+#[wasm_bindgen]
+pub fn desync_report_json() -> Option<String> {
+    desync_report().and_then(|report| serde_json::to_string(&report).ok())
+}
+
+/// Recorded replay as JSON, available once the race is over
+#[wasm_bindgen]
+pub fn replay_json() -> Option<String> {
+    replay().and_then(|replay| serde_json::to_string(&replay).ok())
+}
+
+#[wasm_bindgen]
+pub fn replay_game_raw(replay_json: String, canvas_selector: Option<String>) {
+    match Replay::from_json(&replay_json) {
+        Ok(replay) => replay_game(replay, canvas_selector),
+        Err(error) => println!("{}", error),
+    }
 }
 
 pub fn networked_game(
@@ -43,7 +103,36 @@ pub fn networked_game(
     room_id: RoomId,
     room_config: GameRoomConfig,
     canvas_selector: Option<String>,
-) {
+    track: Option<TrackDefinition>,
+    key_bindings: Option<KeyBindings>,
+    replay_file: Option<PathBuf>,
+    diagnostics_dir: Option<PathBuf>,
+) -> Result<(), String> {
+    let best_of = room_config.best_of;
+    let car_classes = room_config.car_classes;
+
+    // Peers compare the handling they simulate with before the session starts
+    let tuning = room_config.tuning;
+    tuning.validate().map_err(|error| error.to_string())?;
+    if let Some(expected) = room_config.tuning_fingerprint {
+        let fingerprint = tuning.fingerprint();
+        if fingerprint != expected {
+            return Err(format!(
+                "The room was created with car tuning {}, but we received {}, not starting the match",
+                expected, fingerprint
+            ));
+        }
+    }
+
+    // Define spectators, which might include us
+    let is_spectator = room_config.spectators.contains(&user_id);
+    let mut spectators: Vec<NetworkPlayer> = room_config
+        .spectators
+        .iter()
+        .map(|p| NetworkPlayer::spectator(p.clone(), p.clone() == user_id.clone()))
+        .collect();
+    spectators.sort_by_key(|p| p.id.0.to_string().clone());
+
     // Define local and remote players
     let my_network_player = NetworkPlayer {
         id: user_id.clone(),
@@ -60,7 +149,9 @@ pub fn networked_game(
             is_spectator: false,
         })
         .collect();
-    network_players.push(my_network_player);
+    if !is_spectator {
+        network_players.push(my_network_player);
+    }
     network_players.sort_by_key(|p| p.id.0.to_string().clone());
 
     // Define network
@@ -74,10 +165,137 @@ pub fn networked_game(
     };
 
     // Build game
-    let config = GameConfig::default(network, network_players, canvas_selector);
+    let mut config = GameConfig::default(
+        GameMode::Networked(network),
+        network_players,
+        canvas_selector,
+    );
+    config.spectators = spectators;
+    config.best_of = best_of;
+    config.apply_tuning(&tuning);
+    config.car_classes = config
+        .players
+        .iter()
+        .map(|player| car_classes.get(&player.id).copied().unwrap_or_default())
+        .collect();
+    if let Some(track) = track {
+        config.track = track;
+    }
+    if let Some(key_bindings) = key_bindings {
+        config.key_bindings = key_bindings;
+    }
+    config.replay_file = replay_file;
+    config.diagnostics_dir = diagnostics_dir;
     let mut game = App::new();
     build_game(&mut game, config);
 
     // Run game
     game.run();
+
+    // The match never started when peers would have simulated different handling
+    match tuning_mismatch() {
+        Some(mismatch) => Err(mismatch),
+        None => Ok(()),
+    }
+}
+
+/// Runs a local race where every frame is resimulated, returns once the test is over
+pub fn sync_test_game(
+    sync_test: SyncTestConfig,
+    track: Option<TrackDefinition>,
+    tuning: Option<CarTuning>,
+    key_bindings: Option<KeyBindings>,
+    replay_file: Option<PathBuf>,
+) -> Option<SyncTestResult> {
+    // All players are local
+    let players: Vec<NetworkPlayer> = (0..sync_test.player_count)
+        .map(|i| NetworkPlayer::local_player(PlayerId(Uuid::from_u128(i as u128))))
+        .collect();
+
+    // Build game
+    let mut config = GameConfig::default(GameMode::SyncTest(sync_test), players, None);
+    if let Some(track) = track {
+        config.track = track;
+    }
+    if let Some(tuning) = tuning {
+        config.apply_tuning(&tuning);
+    }
+    if let Some(key_bindings) = key_bindings {
+        config.key_bindings = key_bindings;
+    }
+    config.replay_file = replay_file;
+    let mut game = App::new();
+    build_game(&mut game, config);
+
+    // Run game
+    game.run();
+
+    sync_test_result()
+}
+
+/// Plays a recorded race back in a window, with pause, seek and fast-forward
+pub fn replay_game(replay: Replay, canvas_selector: Option<String>) {
+    // Build game
+    let mut config =
+        GameConfig::default(GameMode::Replay, replay.network_players(), canvas_selector);
+    replay.physics.apply(&mut config);
+    config.track = replay.track;
+    let mut game = App::new();
+    build_game(&mut game, config);
+    game.insert_resource(ReplayPlayback::new(replay.inputs));
+
+    // Run game
+    game.run();
+}
+
+/// Steps the simulation through recorded inputs as fast as possible, without a window
+pub fn headless_game(
+    inputs: InputStream,
+    frames: Option<Frame>,
+    track: Option<TrackDefinition>,
+    tuning: Option<CarTuning>,
+) -> HeadlessResult {
+    // All players are local
+    let players: Vec<NetworkPlayer> = (0..inputs.player_count)
+        .map(|i| NetworkPlayer::local_player(PlayerId(Uuid::from_u128(i as u128))))
+        .collect();
+
+    let mut config = GameConfig::default(GameMode::Headless, players, None);
+    if let Some(track) = track {
+        config.track = track;
+    }
+    if let Some(tuning) = tuning {
+        config.apply_tuning(&tuning);
+    }
+
+    run_headless(config, inputs, frames)
+}
+
+/// Resimulates a recorded race without a window, e.g. to reproduce a bug report
+pub fn headless_replay(replay: Replay, frames: Option<Frame>) -> HeadlessResult {
+    let mut config = GameConfig::default(GameMode::Headless, replay.network_players(), None);
+    replay.physics.apply(&mut config);
+    config.track = replay.track;
+
+    run_headless(config, replay.inputs, frames)
+}
+
+fn run_headless(config: GameConfig, inputs: InputStream, frames: Option<Frame>) -> HeadlessResult {
+    // Build game
+    let mut game = App::new();
+    build_headless_game(&mut game, config);
+
+    // Run startup systems, which spawn the scene
+    game.update();
+
+    // Step frames
+    let frames = frames.unwrap_or(inputs.frame_count());
+    for _ in 1..=frames {
+        step_recorded_frame(&mut game.world, &inputs);
+    }
+
+    HeadlessResult {
+        frames,
+        rapier_checksum: game.world.resource::<RapierRollbackState>().rapier_checksum,
+    }
 }
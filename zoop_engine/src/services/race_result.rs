@@ -0,0 +1,20 @@
+use std::sync::Mutex;
+use zoop_shared::race_result::{RaceResult, RACE_RESULT_PREFIX};
+
+static RACE_RESULT: Mutex<Option<RaceResult>> = Mutex::new(None);
+
+/// Hands the final race result over to whoever launched the game
+pub fn publish_race_result(result: RaceResult) {
+    // Native launchers read this line from the game's stdout
+    if let Ok(json) = serde_json::to_string(&result) {
+        println!("{}{}", RACE_RESULT_PREFIX, json);
+    }
+    // The browser asks for it through interop instead
+    if let Ok(mut stored) = RACE_RESULT.lock() {
+        *stored = Some(result);
+    }
+}
+
+pub fn race_result() -> Option<RaceResult> {
+    RACE_RESULT.lock().ok().and_then(|result| result.clone())
+}
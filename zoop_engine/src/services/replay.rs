@@ -0,0 +1,25 @@
+use crate::domain::replay::Replay;
+use bevy::prelude::*;
+use std::path::Path;
+use std::sync::Mutex;
+
+static REPLAY: Mutex<Option<Replay>> = Mutex::new(None);
+
+/// Hands the recorded replay over to whoever launched the game
+pub fn publish_replay(replay: Replay, path: Option<&Path>) {
+    // Native launchers ask for a file
+    if let Some(path) = path {
+        match replay.to_file(path) {
+            Ok(()) => info!("Saved replay to {}", path.display()),
+            Err(error) => error!("{}", error),
+        }
+    }
+    // The browser asks for it through interop instead
+    if let Ok(mut stored) = REPLAY.lock() {
+        *stored = Some(replay);
+    }
+}
+
+pub fn replay() -> Option<Replay> {
+    REPLAY.lock().ok().and_then(|replay| replay.clone())
+}
@@ -0,0 +1,20 @@
+use crate::domain::sync_test::SyncTestResult;
+use std::sync::Mutex;
+
+static SYNC_TEST_RESULT: Mutex<Option<SyncTestResult>> = Mutex::new(None);
+
+/// Keeps the first result, later ones are consequences of it
+pub fn publish_sync_test_result(result: SyncTestResult) {
+    if let Ok(mut stored) = SYNC_TEST_RESULT.lock() {
+        if stored.is_none() {
+            *stored = Some(result);
+        }
+    }
+}
+
+pub fn sync_test_result() -> Option<SyncTestResult> {
+    SYNC_TEST_RESULT
+        .lock()
+        .ok()
+        .and_then(|result| result.clone())
+}
@@ -0,0 +1,17 @@
+use std::sync::Mutex;
+
+static TUNING_MISMATCH: Mutex<Option<String>> = Mutex::new(None);
+
+/// Keeps the reason the match didn't start, for whoever launched the game
+pub fn publish_tuning_mismatch(message: String) {
+    if let Ok(mut stored) = TUNING_MISMATCH.lock() {
+        *stored = Some(message);
+    }
+}
+
+pub fn tuning_mismatch() -> Option<String> {
+    TUNING_MISMATCH
+        .lock()
+        .ok()
+        .and_then(|message| message.clone())
+}
@@ -4,14 +4,17 @@ use std::fmt;
 use std::fmt::Formatter;
 use std::sync::{Arc, Mutex};
 use zoop_shared::player_id::PlayerId;
-use zoop_shared::player_message::PlayerMessage;
+use zoop_shared::player_message::{PlayerMessage, PlayerMessageKind};
 
-/// A simple non-blocking WebSocket connection to use with GGRS Sessions
-#[derive(Debug)]
+/// A simple non-blocking WebSocket connection to use with GGRS Sessions.
+/// Clones share the connection, so that it outlives a session.
+#[derive(Debug, Clone)]
 pub struct NonBlockingWebSocket {
     address: String,
     sender: Arc<Mutex<WrappedWsSender>>,
     receiver: Arc<Mutex<WrappedWsReceiver>>,
+    resync_messages: Arc<Mutex<Vec<(PlayerId, String)>>>,
+    handshake_messages: Arc<Mutex<Vec<(PlayerId, String)>>>,
 }
 
 // Might blow up
@@ -65,8 +68,45 @@ impl NonBlockingWebSocket {
             address,
             sender: wrapped_sender,
             receiver: wrapped_receiver,
+            resync_messages: Arc::new(Mutex::new(Vec::new())),
+            handshake_messages: Arc::new(Mutex::new(Vec::new())),
         })
     }
+
+    /// Sends a resynchronization message, which is not meant for GGRS
+    pub fn send_resync(&self, message: String, addr: &PlayerId) {
+        self.send_player_message(PlayerMessage::resync_to(addr.clone(), message));
+    }
+
+    /// Sends a handshake message, which is not meant for GGRS
+    pub fn send_handshake(&self, message: String, addr: &PlayerId) {
+        self.send_player_message(PlayerMessage::handshake_to(addr.clone(), message));
+    }
+
+    fn send_player_message(&self, player_message: PlayerMessage) {
+        if let Ok(mut writer) = self.sender.lock() {
+            let player_message = serde_json::to_string(&player_message).unwrap();
+            if writer.opened {
+                writer.underlying.send(WsMessage::Text(player_message));
+            }
+        }
+    }
+
+    /// Resynchronization messages received so far, whoever drained the connection
+    pub fn take_resync_messages(&self) -> Vec<(PlayerId, String)> {
+        self.resync_messages
+            .lock()
+            .map(|mut messages| std::mem::take(&mut *messages))
+            .unwrap_or_default()
+    }
+
+    /// Handshake messages received so far, whoever drained the connection
+    pub fn take_handshake_messages(&self) -> Vec<(PlayerId, String)> {
+        self.handshake_messages
+            .lock()
+            .map(|mut messages| std::mem::take(&mut *messages))
+            .unwrap_or_default()
+    }
 }
 
 impl NonBlockingSocket<PlayerId> for NonBlockingWebSocket {
@@ -111,9 +151,26 @@ impl NonBlockingSocket<PlayerId> for NonBlockingWebSocket {
                         let from_player_message: PlayerMessage =
                             serde_json::from_str(text.as_str()).unwrap();
                         let from_address = from_player_message.address;
-                        let message: Message =
-                            serde_json::from_str(from_player_message.message.as_str()).unwrap();
-                        received_messages.push((from_address, message));
+                        match from_player_message.kind {
+                            PlayerMessageKind::Ggrs => {
+                                let message: Message =
+                                    serde_json::from_str(from_player_message.message.as_str())
+                                        .unwrap();
+                                received_messages.push((from_address, message));
+                            }
+                            PlayerMessageKind::Resync => {
+                                if let Ok(mut resync_messages) = self.resync_messages.lock() {
+                                    resync_messages
+                                        .push((from_address, from_player_message.message));
+                                }
+                            }
+                            PlayerMessageKind::Handshake => {
+                                if let Ok(mut handshake_messages) = self.handshake_messages.lock() {
+                                    handshake_messages
+                                        .push((from_address, from_player_message.message));
+                                }
+                            }
+                        }
                     }
                     WsEvent::Error(e) => panic!("Websocket error for {}: {:?}", &self.address, e),
                     WsEvent::Closed => panic!("Websocket closed for {:?}", &self.address),
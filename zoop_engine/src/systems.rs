@@ -1,7 +1,19 @@
 pub mod build_game;
 pub mod build_network;
+pub mod collision_damage;
 pub mod drive_car;
+pub mod follow_camera;
+pub mod handle_desync;
+pub mod hud;
 pub mod manage_scene;
+pub mod race_progress;
+pub mod race_start;
 pub mod read_controls;
+pub mod replay_playback;
+pub mod replay_recording;
+pub mod restart_round;
+pub mod resync;
 pub mod rollback_rapier_context;
 pub mod save_rapier_context;
+pub mod sync_test;
+pub mod tuning_handshake;
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use bevy::winit::WinitSettings;
 use bevy_ggrs::*;
 #[cfg(feature = "world_debug")]
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
@@ -8,15 +9,34 @@ use bevy_rapier2d::prelude::*;
 
 use crate::domain::colors::*;
 use crate::domain::desync::*;
+use crate::domain::frame_inputs::*;
 use crate::domain::frames::*;
 use crate::domain::game_config::GameConfig;
+use crate::domain::game_font::GameFont;
+use crate::domain::game_mode::GameMode;
 use crate::domain::game_set::GameSet;
+use crate::domain::rapier_rollback_state::{PhysicsStateBuffers, RoundStartPhysics};
+use crate::domain::replay::InputRecorder;
+use crate::domain::resync::Resync;
 use crate::domain::spawn::*;
+use crate::domain::sync_test::SyncTestChecksums;
 use crate::systems::build_network::*;
+use crate::systems::collision_damage::*;
 use crate::systems::drive_car::*;
+use crate::systems::follow_camera::*;
+use crate::systems::handle_desync::*;
+use crate::systems::hud::*;
 use crate::systems::manage_scene::*;
+use crate::systems::race_progress::*;
+use crate::systems::race_start::*;
+use crate::systems::replay_playback::*;
+use crate::systems::replay_recording::*;
+use crate::systems::restart_round::*;
+use crate::systems::resync::*;
 use crate::systems::rollback_rapier_context::*;
 use crate::systems::save_rapier_context::*;
+use crate::systems::sync_test::*;
+use crate::systems::tuning_handshake::*;
 
 pub fn build_game(game: &mut App, config: GameConfig) {
     // Log panics in browser console
@@ -29,16 +49,8 @@ pub fn build_game(game: &mut App, config: GameConfig) {
 
     info!("Starting game with config {:?}", config);
 
-    // Pre-spawn entities which will be re-used as game entities
-    // for some reason Rapier requires these to be deterministic
-    let _ = game
-        .world
-        .spawn_batch((0..101).map(DeterministicSpawnBundle::new))
-        .collect::<Vec<Entity>>();
-
-    // Generic game resources
-    game.insert_resource(config.clone())
-        .insert_resource(ClearColor(ZOOP_YELLOW));
+    // Must happen before any plugin spawns entities
+    prespawn_entities(game, &config);
 
     // Default Bevy plugins
     game.add_plugins(DefaultPlugins.set(WindowPlugin {
@@ -49,6 +61,59 @@ pub fn build_game(game: &mut App, config: GameConfig) {
         ..default()
     }));
 
+    // Sync tests report back to the CLI once they're done
+    if let GameMode::SyncTest(_) = config.mode {
+        game.insert_resource(WinitSettings {
+            return_from_run: true,
+            ..default()
+        });
+    }
+
+    build_simulation(game, &config);
+    build_presentation(game, &config);
+}
+
+/// Simulation only, without a window, renderer or GPU.
+/// The caller steps the `GGRSSchedule` itself.
+pub fn build_headless_game(game: &mut App, config: GameConfig) {
+    info!("Starting headless game with config {:?}", config);
+
+    // Must happen before any plugin spawns entities
+    prespawn_entities(game, &config);
+
+    // Bare minimum Bevy plugins for Rapier
+    game.add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(AssetPlugin::default())
+        // Rapier looks up meshes for async colliders
+        .add_asset::<Mesh>();
+
+    build_simulation(game, &config);
+}
+
+fn prespawn_entities(game: &mut App, config: &GameConfig) {
+    // Pre-spawn entities which will be re-used as game entities
+    // for some reason Rapier requires these to be deterministic.
+    // Every peer has the same config, so they get the same entities.
+    let count = init_scene(config).spawn_count();
+    let _ = game
+        .world
+        .spawn_batch((0..count).map(DeterministicSpawnBundle::new))
+        .collect::<Vec<Entity>>();
+}
+
+/// Physics, game logic and rollback, identical for every peer
+fn build_simulation(game: &mut App, config: &GameConfig) {
+    // Generic game resources
+    game.insert_resource(config.clone())
+        .insert_resource(config.key_bindings.clone())
+        .insert_resource(FrameInputs::default());
+
+    if let GameMode::SyncTest(_) = config.mode {
+        game.insert_resource(SyncTestChecksums::default());
+    }
+
     // Physics plugin
     game.insert_resource(config.rapier_config());
     game.add_plugin(
@@ -56,23 +121,12 @@ pub fn build_game(game: &mut App, config: GameConfig) {
             .with_default_system_setup(false),
     );
 
-    // Debug line renderer
-    #[cfg(feature = "debug_lines")]
-    game.add_plugin(DebugLinesPlugin::default());
-
-    // Debug physics renderer
-    #[cfg(feature = "rapier_debug_physics")]
-    game.add_plugin(RapierDebugRenderPlugin::default());
-
-    // Debug world inspector
-    #[cfg(feature = "world_debug")]
-    game.add_plugin(WorldInspectorPlugin::new());
-
     // Init rollback & desync resources
     // frame updating
     game.insert_resource(LastFrame::default());
     game.insert_resource(CurrentFrame::default());
     game.insert_resource(CurrentSessionFrame::default());
+    game.insert_resource(SessionStartFrame::default());
     game.insert_resource(ConfirmedFrame::default());
     game.insert_resource(RollbackStatus::default());
     game.insert_resource(ValidatableFrame::default());
@@ -80,25 +134,30 @@ pub fn build_game(game: &mut App, config: GameConfig) {
     // desync detection
     game.insert_resource(FrameHashes::default());
     game.insert_resource(RxFrameHashes::default());
+    game.insert_resource(Desynced::default());
+    game.add_event::<DesyncEvent>();
+    game.add_system(handle_desync);
 
     // physics toggling
     game.insert_resource(EnablePhysicsAfter::with_default_offset(
         0,
         config.fps as i32,
-        config.load_seconds as i32,
+        (config.load_seconds + config.countdown_seconds) as i32,
     ));
     game.insert_resource(PhysicsEnabled::default());
 
     // Reset rapier
+    game.insert_resource(PhysicsStateBuffers::default());
+    game.insert_resource(RoundStartPhysics::default());
     game.add_startup_system(reset_rapier);
 
     // Init game state
-    let state = init_scene(&config);
+    let state = init_scene(config);
     game.insert_resource(state);
     game.add_startup_system(setup_scene);
 
     // Configure networking
-    build_network(game, &config);
+    build_network(game, config);
 
     // Synchronized game logic stage
     let game_schedule = GGRSSchedule;
@@ -122,6 +181,8 @@ pub fn build_game(game: &mut App, config: GameConfig) {
         .add_systems(
             (
                 update_current_frame,
+                update_frame_inputs,
+                record_inputs,
                 update_current_session_frame,
                 update_confirmed_frame,
                 // the three above must actually come before we update rollback status
@@ -140,10 +201,13 @@ pub fn build_game(game: &mut App, config: GameConfig) {
             (
                 // destroy_scene,
                 // setup_scene,
+                restart_round,
+                detect_false_starts,
                 drive_car,
+                update_race_progress,
                 // The `frame_validator` relies on the execution of `apply_inputs` and must come after.
                 // It could happen anywhere else, I just stuck it here to be clear.
-                // If this is causing your game to quit, you have a bug!
+                // If this is ending your game, you have a bug!
                 frame_validator,
                 force_update_rollbackables,
                 // Make sure to flush everything before Rapier syncs
@@ -181,6 +245,8 @@ pub fn build_game(game: &mut App, config: GameConfig) {
         )
         .add_systems(
             (
+                apply_collision_damage, // Reads the contact forces of the step that just happened
+                store_scene,            // Copies the written back bodies into the game state
                 save_rapier_context, // This must execute after writeback to store the RapierContext
                 apply_system_buffers, // Flushing again
             )
@@ -188,13 +254,87 @@ pub fn build_game(game: &mut App, config: GameConfig) {
                 .in_base_set(GameSet::SaveAndChecksum),
         );
 
-    // Scene setup
-    game.add_startup_system(setup_graphics);
+    // Determinism checks
+    if let GameMode::SyncTest(_) = config.mode {
+        game.get_schedule_mut(GGRSSchedule).unwrap().add_system(
+            check_sync_test
+                .after(save_rapier_context)
+                .in_base_set(GameSet::SaveAndChecksum),
+        );
+    }
+
+    // Agreement on the car tuning before the session starts
+    if let GameMode::Networked(_) = config.mode {
+        game.add_system(process_tuning_handshake.before(process_resync));
+    }
+
+    // Recovery from desyncs
+    if let GameMode::Networked(_) = config.mode {
+        game.insert_resource(Resync::default());
+        game.add_system(process_resync.after(handle_desync));
+        game.get_schedule_mut(GGRSSchedule).unwrap().add_system(
+            record_resync_snapshot
+                .after(save_rapier_context)
+                .in_base_set(GameSet::SaveAndChecksum),
+        );
+    }
+
+    // Race results
+    game.add_system(publish_race_result_when_confirmed);
+
+    // Replays
+    if config.is_recording() {
+        game.insert_resource(InputRecorder::new(config.players.len()));
+        game.add_system(save_replay.in_base_set(CoreSet::Last));
+    }
+    if let GameMode::Replay = config.mode {
+        game.add_system(control_replay.before(play_replay));
+        game.add_system(play_replay);
+    }
 }
 
-fn setup_graphics(mut commands: Commands) {
-    let bundle = Camera2dBundle::default();
-    commands.spawn(bundle);
+/// Everything that is drawn, none of it affects the simulation
+fn build_presentation(game: &mut App, config: &GameConfig) {
+    game.insert_resource(ClearColor(ZOOP_YELLOW));
+
+    // Embedded font for all in-game text
+    let font = GameFont::load(game.world.resource_mut::<Assets<Font>>().as_mut());
+    game.insert_resource(font);
+
+    // Debug line renderer
+    #[cfg(feature = "debug_lines")]
+    game.add_plugin(DebugLinesPlugin::default());
+
+    // Debug physics renderer
+    #[cfg(feature = "rapier_debug_physics")]
+    game.add_plugin(RapierDebugRenderPlugin::default());
+
+    // Debug world inspector
+    #[cfg(feature = "world_debug")]
+    game.add_plugin(WorldInspectorPlugin::new());
+
+    // Scene setup
+    game.add_startup_system(setup_camera);
+    game.add_startup_system(setup_countdown);
+    game.add_system(update_countdown);
+
+    // Desync notice
+    game.add_startup_system(setup_desync_notice);
+    game.add_system(update_desync_notice);
+
+    // HUD
+    game.add_startup_system(setup_hud);
+    game.add_system(toggle_hud);
+    game.add_system(update_hud);
+
+    // Camera
+    game.add_system(follow_local_car);
+
+    // Replay controls
+    if let GameMode::Replay = config.mode {
+        game.add_startup_system(setup_replay_overlay);
+        game.add_system(update_replay_overlay);
+    }
 }
 
 fn rapier_stub() {}
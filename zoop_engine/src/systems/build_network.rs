@@ -1,8 +1,14 @@
 use crate::domain::frames::CurrentFrame;
 use crate::domain::game_config::GameConfig;
+use crate::domain::game_mode::GameMode;
 use crate::domain::game_state::GameState;
 use crate::domain::ggrs_config::GGRSConfig;
+use crate::domain::handshake::TuningHandshake;
 use crate::domain::rapier_rollback_state::RapierRollbackState;
+use crate::domain::resync::{ResyncHistory, RoomSocket};
+use crate::domain::room_config::RoomConfig;
+use crate::domain::sync_test::SyncTestConfig;
+use crate::domain::tire::TirePhysics;
 use crate::services::websocket::*;
 use crate::systems::read_controls::read_controls;
 use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
@@ -12,9 +18,31 @@ use bevy_rapier2d::dynamics::{Sleeping, Velocity};
 use ggrs::*;
 
 pub fn build_network(game: &mut App, config: &GameConfig) {
-    let session = start_network_session(&config);
+    let session = match &config.mode {
+        // Inputs are stepped through by the caller or the replay player
+        GameMode::Headless | GameMode::Replay => {
+            build_ggrs(game, config);
+            return;
+        }
+        GameMode::Networked(room) if config.is_spectating() => {
+            Session::SpectatorSession(start_spectator_session(config, room))
+        }
+        GameMode::Networked(room) => {
+            // Kept around to resynchronize through, if the session gets lost to a desync
+            let socket = connect_room(room);
+            game.insert_resource(RoomSocket(socket.clone()));
+            game.insert_resource(ResyncHistory::default());
+            // The session starts once every peer agreed on the car tuning
+            game.insert_resource(TuningHandshake::of(config));
+            build_ggrs(game, config);
+            return;
+        }
+        GameMode::SyncTest(sync_test) => {
+            Session::SyncTestSession(start_sync_test_session(config, sync_test))
+        }
+    };
     build_ggrs(game, config);
-    game.insert_resource(Session::P2PSession(session));
+    game.insert_resource(session);
 }
 
 pub fn build_ggrs(game: &mut App, config: &GameConfig) {
@@ -59,11 +87,20 @@ pub fn build_ggrs(game: &mut App, config: &GameConfig) {
         // .register_rollback_component::<Transform>()
         // # game
         // .register_rollback_component::<TireMeta>()
+        .register_rollback_component::<TirePhysics>()
         // these systems will be executed as part of the advance frame update
         .build(game);
 }
 
-pub fn start_network_session(config: &GameConfig) -> P2PSession<GGRSConfig> {
+pub fn connect_room(room: &RoomConfig) -> NonBlockingWebSocket {
+    let room_address = room.game_room_address().unwrap();
+    NonBlockingWebSocket::connect(room_address.to_string()).unwrap()
+}
+
+pub fn start_network_session(
+    config: &GameConfig,
+    socket: NonBlockingWebSocket,
+) -> P2PSession<GGRSConfig> {
     // Create a GGRS session
     let mut session_builder = SessionBuilder::<GGRSConfig>::new()
         .with_num_players(config.players.len())
@@ -84,9 +121,50 @@ pub fn start_network_session(config: &GameConfig) -> P2PSession<GGRSConfig> {
         }
     }
 
-    // Start the GGRS session
-    let room_address = config.game_room_address().unwrap();
-    let socket = NonBlockingWebSocket::connect(room_address.to_string()).unwrap();
+    // Add spectators, handles come after the players
+    if config.is_host() {
+        for (i, spectator) in config.spectators.iter().enumerate() {
+            session_builder = session_builder
+                .add_player(
+                    PlayerType::Spectator(spectator.id.clone()),
+                    config.players.len() + i,
+                )
+                .unwrap();
+        }
+    }
 
+    // Start the GGRS session
     session_builder.start_p2p_session(socket).unwrap()
 }
+
+pub fn start_spectator_session(
+    config: &GameConfig,
+    room: &RoomConfig,
+) -> SpectatorSession<GGRSConfig> {
+    // The host sends confirmed inputs of all players to spectators
+    let host = config
+        .players
+        .first()
+        .expect("Spectated room must have players")
+        .id
+        .clone();
+
+    let socket = connect_room(room);
+
+    SessionBuilder::<GGRSConfig>::new()
+        .with_num_players(config.players.len())
+        .start_spectator_session(host, socket)
+}
+
+pub fn start_sync_test_session(
+    config: &GameConfig,
+    sync_test: &SyncTestConfig,
+) -> SyncTestSession<GGRSConfig> {
+    // Every player is local, each frame gets rolled back and resimulated
+    SessionBuilder::<GGRSConfig>::new()
+        .with_num_players(config.players.len())
+        .with_max_prediction_window(12)
+        .with_check_distance(sync_test.check_distance)
+        .start_synctest_session()
+        .expect("Sync test check distance must be smaller than the prediction window")
+}
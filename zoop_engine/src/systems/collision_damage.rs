@@ -0,0 +1,43 @@
+use crate::domain::car_body::CarMeta;
+use crate::domain::frames::CurrentFrame;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_state::GameState;
+use crate::domain::player::Player;
+use crate::domain::tire::TireMeta;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Turns the contact forces of this frame's step into damage kept in the game state.
+/// Runs right after the step, so that a rollback never sees events of a discarded frame.
+pub fn apply_collision_damage(
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
+    mut contact_force_events: EventReader<ContactForceEvent>,
+    // Tires stick out of the body, a hit on a tire damages its car
+    body_query: Query<&Player, Or<(With<CarMeta>, With<TireMeta>)>>,
+    mut state: ResMut<GameState>,
+) {
+    let frame = current_frame.0;
+    for event in contact_force_events.iter() {
+        let car1 = body_query.get(event.collider1).ok().map(|p| p.handle);
+        let car2 = body_query.get(event.collider2).ok().map(|p| p.handle);
+        // A car pushing against its own tires isn't a collision
+        if car1.is_some() && car1 == car2 {
+            continue;
+        }
+        let is_hit = car1.is_some() && car2.is_some();
+        // Force over one step, the same for both sides of the contact
+        let impulse = event.total_force_magnitude / config.fps as f32;
+
+        for handle in [car1, car2].into_iter().flatten() {
+            let Some(car) = state.cars_mut().find(|car| car.player.handle == handle) else {
+                continue;
+            };
+            car.damage
+                .take(impulse, config.collision_damage_per_impulse, frame);
+            if is_hit {
+                car.damage.record_hit(frame);
+            }
+        }
+    }
+}
@@ -1,23 +1,32 @@
 use crate::domain::car_body::CarMeta;
+use crate::domain::car_class::CarSpec;
+use crate::domain::controls::Controls;
 
 use crate::domain::desync::*;
+use crate::domain::frame_inputs::FrameInputs;
+use crate::domain::frames::CurrentFrame;
 use crate::domain::game_config::GameConfig;
-use crate::domain::ggrs_config::GGRSConfig;
+use crate::domain::game_state::GameState;
 use crate::domain::player::Player;
+use crate::domain::surface::surface_traits;
 use crate::domain::tire::{TireMeta, TirePhysics};
 use crate::logic::math::*;
 use crate::logic::movement::*;
+use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
 
 use bevy::prelude::*;
-use bevy_ggrs::*;
 #[cfg(feature = "debug_lines")]
 use bevy_prototype_debug_lines::*;
 use bevy_rapier2d::prelude::*;
 
 pub fn drive_car(
     config: Res<GameConfig>,
-    inputs: Res<PlayerInputs<GGRSConfig>>,
+    inputs: Res<FrameInputs>,
+    current_frame: Res<CurrentFrame>,
+    enable_physics_after: Res<EnablePhysicsAfter>,
+    state: Res<GameState>,
     mut hashes: ResMut<RxFrameHashes>,
+    mut desyncs: EventWriter<DesyncEvent>,
     mut source_car_query: Query<(&CarMeta, &Transform, &Player), Without<TireMeta>>,
     mut source_tire_query: Query<
         (
@@ -74,7 +83,7 @@ pub fn drive_car(
         tire_player,
     ) in tire_query
     {
-        let (game_input, input_status) = inputs[tire_player.handle];
+        let (game_input, input_status) = inputs.0[tire_player.handle];
         if tire_meta.is_front && tire_meta.is_right {
             // Check the desync for this player if they're not a local handle
             // Did they send us some goodies?
@@ -86,15 +95,21 @@ pub fn drive_car(
                 .is_some();
             if !is_local && game_input.last_confirmed_frame > 0 {
                 info!("Got frame data {:?}", game_input);
-                if let Some(frame_hash) = hashes.0.get_mut(
+                if let Some(frame_hash) = hashes.of_handle_mut(tire_player.handle).get_mut(
                     (game_input.last_confirmed_frame as usize) % config.desync_max_frames as usize,
                 ) {
-                    assert!(
-                        frame_hash.frame != game_input.last_confirmed_frame
-                            || frame_hash.rapier_checksum == game_input.last_confirmed_hash,
-                        "Got new data for existing frame data {}",
-                        frame_hash.frame
-                    );
+                    if frame_hash.frame == game_input.last_confirmed_frame
+                        && frame_hash.rapier_checksum != game_input.last_confirmed_hash
+                    {
+                        error!("Got new data for existing frame data {}", frame_hash.frame);
+                        desyncs.send(DesyncEvent {
+                            source: DesyncSource::PeerConflict,
+                            frame: frame_hash.frame,
+                            peer: Some(tire_player.handle),
+                            local_checksum: frame_hash.rapier_checksum,
+                            remote_checksum: game_input.last_confirmed_hash,
+                        });
+                    }
 
                     // Only update this local data if the frame is new-to-us.
                     // We don't want to overwrite any existing validated status
@@ -114,7 +129,28 @@ pub fn drive_car(
                 input_status, tire_player.handle, game_input.input
             )
         }
-        let controls = game_input;
+        let penalty_frames = config.false_start_penalty_seconds as i32 * config.fps as i32;
+        let car = state
+            .cars()
+            .find(|car| car.player.handle == tire_player.handle);
+        let is_held = car
+            .map(|car| {
+                car.progress
+                    .is_held(current_frame.0, enable_physics_after.end, penalty_frames)
+            })
+            .unwrap_or(true);
+        let spec = car
+            .map(|car| car.spec.clone())
+            .unwrap_or_else(|| CarSpec::of(config.car_class(tire_player.handle), &config));
+        let damage = car.map(|car| car.damage).unwrap_or_default();
+        let controls = if is_held {
+            Controls::empty(
+                game_input.last_confirmed_hash,
+                game_input.last_confirmed_frame,
+            )
+        } else {
+            game_input
+        };
 
         // Apply controls to tire angle
         tire_physics.angle = tire_physics.angle
@@ -122,8 +158,10 @@ pub fn drive_car(
                 tire_meta,
                 tire_physics.as_ref(),
                 &controls,
-                config.tire_max_angle,
-                config.tire_rotation_per_tick,
+                spec.tire_max_angle,
+                spec.tire_rotation_per_tick,
+                spec.rear_steering_ratio,
+                damage.steering_bend(config.damage_max_steering_bend),
             );
 
         // Apply tire angle to tire rotation transform
@@ -150,21 +188,51 @@ pub fn drive_car(
         }
         transform.rotation = Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, tire_rotation);
 
+        // Look up the surface under the tire, the track is static so this is deterministic
+        let surface = surface_traits(
+            config
+                .track
+                .surface_at(transform.translation.truncate() / config.pixels_per_meter),
+        );
+
         // Apply tire acceleration
+        let acceleration = tire_acceleration(
+            &tire_meta,
+            &controls,
+            config.pixels_per_meter,
+            direction_velocity,
+            spec.acceleration_force,
+            spec.reversing_force,
+            spec.breaking_force,
+            spec.drive_share(tire_meta) * damage.power_share(config.damage_max_power_loss),
+        );
         forcable.force = tire_direction
-            * tire_acceleration(
-                &tire_meta,
-                &controls,
-                config.pixels_per_meter,
+            * surface_limited_force(
+                acceleration,
                 direction_velocity,
-                config.tire_acceleration_force,
-                config.tire_reversing_force,
-                config.tire_breaking_force,
+                surface
+                    .max_speed
+                    .map(|max_speed| max_speed * config.pixels_per_meter),
             );
 
-        // Apply friction
+        // Apply rolling resistance of the surface
+        impulsable.impulse += rolling_resistance_impulse(
+            surface.rolling_resistance,
+            &tire_direction,
+            direction_velocity,
+        );
+
+        // Apply friction, the handbrake lets the rear slide
+        let handbrake_grip = if controls.handbraking() && !tire_meta.is_front {
+            config.tire_handbrake_grip
+        } else {
+            1.0
+        };
+        let grip = handbrake_grip * surface.grip;
         let friction_impulse = tire_friction_impulse(
             config.tire_friction_force,
+            config.tire_half_grip_slip_angle,
+            grip,
             &tire_direction,
             &velocity.linvel,
         );
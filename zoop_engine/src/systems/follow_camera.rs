@@ -0,0 +1,50 @@
+use crate::domain::car_body::CarMeta;
+use crate::domain::game_config::GameConfig;
+use crate::domain::player::Player;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+#[derive(Component)]
+pub struct FollowCamera;
+
+pub fn setup_camera(mut commands: Commands) {
+    commands.spawn((FollowCamera, Camera2dBundle::default()));
+}
+
+/// Follows the local car, runs every render frame outside of the rollback schedule
+/// so that corrected car positions are smoothed over instead of snapped to
+pub fn follow_local_car(
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    car_query: Query<(&Transform, &Velocity, &Player), (With<CarMeta>, Without<FollowCamera>)>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<FollowCamera>>,
+    mut is_following: Local<bool>,
+) {
+    let followed_handle = config.followed_handle();
+    let Some((car_transform, car_velocity, _)) = car_query
+        .iter()
+        .find(|(_, _, player)| player.handle == followed_handle)
+    else {
+        return;
+    };
+
+    let look_ahead = car_velocity.linvel * config.camera_look_ahead_seconds;
+    let target = car_transform.translation.truncate() + look_ahead;
+    let speed = car_velocity.linvel.length() / config.pixels_per_meter;
+    let target_zoom = (1.0 + speed * config.camera_zoom_per_speed).min(config.camera_max_zoom);
+
+    // Frame-rate independent exponential smoothing, snap on the first frame
+    let blend = if *is_following {
+        1.0 - (-config.camera_smoothing * time.delta_seconds()).exp()
+    } else {
+        1.0
+    };
+    *is_following = true;
+
+    for (mut camera_transform, mut projection) in camera_query.iter_mut() {
+        let position = camera_transform.translation.truncate().lerp(target, blend);
+        camera_transform.translation.x = position.x;
+        camera_transform.translation.y = position.y;
+        projection.scale += (target_zoom - projection.scale) * blend;
+    }
+}
@@ -0,0 +1,179 @@
+use crate::domain::colors::*;
+use crate::domain::desync::*;
+use crate::domain::desync_report::DesyncReport;
+use crate::domain::frames::CurrentFrame;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_font::GameFont;
+use crate::domain::game_mode::GameMode;
+use crate::domain::ggrs_config::GGRSConfig;
+use crate::domain::rapier_rollback_state::{PhysicsStateBuffers, RapierRollbackState};
+use crate::domain::replay::InputRecorder;
+use crate::domain::resync::Resync;
+use crate::domain::sync_test::SyncTestResult;
+use crate::services::desync_report::publish_desync_report;
+use crate::services::sync_test::publish_sync_test_result;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_ggrs::Session;
+
+#[derive(Component)]
+pub struct DesyncNotice;
+
+/// Stops the match on the first desync, after writing down what we know about it.
+/// Networked matches try to continue afterwards, see `process_resync`.
+pub fn handle_desync(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
+    rapier_state: Res<RapierRollbackState>,
+    buffers: Res<PhysicsStateBuffers>,
+    hashes: Res<FrameHashes>,
+    recorder: Option<Res<InputRecorder>>,
+    mut desyncs: EventReader<DesyncEvent>,
+    mut desynced: ResMut<Desynced>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(desync) = desyncs.iter().next().cloned() else {
+        return;
+    };
+    desyncs.clear();
+    if desynced.desync.is_some() {
+        return;
+    }
+    error!("{}, stopping the match", desync);
+
+    // Without a session nothing gets simulated anymore, but everything is still rendered
+    commands.remove_resource::<Session<GGRSConfig>>();
+
+    // Inputs from a bit before the desync, the state only diverged eventually
+    let first_input_frame = (desync.frame - config.desync_max_frames as i32).max(1);
+    let inputs = recorder
+        .map(|recorder| {
+            recorder
+                .0
+                .frames
+                .iter()
+                .skip((first_input_frame - 1) as usize)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    let report = DesyncReport {
+        desync: desync.clone(),
+        players: config.players.iter().map(|p| p.id.clone()).collect(),
+        local_handles: (0..config.players.len())
+            .filter(|handle| config.players[*handle].is_local)
+            .collect(),
+        peer: desync
+            .peer
+            .and_then(|handle| config.players.get(handle))
+            .map(|p| p.id.clone()),
+        current_frame: current_frame.0,
+        rapier_checksum: rapier_state.rapier_checksum,
+        state_checksums: hashes
+            .0
+            .iter()
+            .find(|hash| hash.frame == desync.frame)
+            .map(|hash| hash.state_checksums),
+        rapier_state: buffers
+            .get(rapier_state.frame)
+            .map(<[u8]>::to_vec)
+            .unwrap_or_default(),
+        first_input_frame,
+        inputs,
+    };
+    let report_path = publish_desync_report(report, config.diagnostics_dir.as_deref());
+    if let Some(path) = report_path.as_ref() {
+        info!("Saved desync report to {}", path.display());
+    }
+
+    // Sync tests report back to the CLI
+    if let GameMode::SyncTest(_) = config.mode {
+        publish_sync_test_result(SyncTestResult::Mismatch {
+            frame: desync.frame,
+            checksum: desync.remote_checksum,
+            resimulated_checksum: desync.local_checksum,
+        });
+        exit.send(AppExit);
+    }
+
+    desynced.desync = Some(desync);
+    desynced.report_path = report_path;
+}
+
+pub fn setup_desync_notice(mut commands: Commands, font: Res<GameFont>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                DesyncNotice,
+                TextBundle {
+                    visibility: Visibility::Hidden,
+                    ..TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: font.0.clone(),
+                            font_size: 24.0,
+                            color: ZOOP_DARK_RED,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::bottom(Val::Px(40.0)),
+                        ..default()
+                    })
+                },
+            ));
+        });
+}
+
+pub fn update_desync_notice(
+    desynced: Res<Desynced>,
+    resync: Option<Res<Resync>>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<DesyncNotice>>,
+) {
+    let resync_changed = resync.as_ref().map(|r| r.is_changed()).unwrap_or(false);
+    if !desynced.is_changed() && !resync_changed {
+        return;
+    }
+    let Some(desync) = desynced.desync.as_ref() else {
+        // Resynchronized, or nothing happened yet
+        for (_, mut visibility) in text_query.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+
+    let is_recovering = resync
+        .as_ref()
+        .map(|r| r.is_active() || !r.gave_up)
+        .unwrap_or(false);
+    let notice = if is_recovering {
+        format!(
+            "The race went out of sync on frame {}\nResynchronizing with the other players...",
+            desync.frame
+        )
+    } else {
+        let mut notice = format!(
+            "The race went out of sync on frame {} and was stopped",
+            desync.frame
+        );
+        if let Some(path) = desynced.report_path.as_ref() {
+            notice.push_str(&format!("\nDiagnostics saved to {}", path.display()));
+        }
+        notice.push_str("\nClose the game to return to the lobby");
+        notice
+    };
+
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        text.sections[0].value = notice.clone();
+        *visibility = Visibility::Inherited;
+    }
+}
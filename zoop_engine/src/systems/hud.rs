@@ -0,0 +1,183 @@
+use crate::domain::car_body::CarMeta;
+use crate::domain::colors::*;
+use crate::domain::frames::*;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_font::GameFont;
+use crate::domain::game_state::GameState;
+use crate::domain::ggrs_config::GGRSConfig;
+use crate::domain::handshake::TuningHandshake;
+use crate::domain::player::Player;
+use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
+use bevy::prelude::*;
+use bevy_ggrs::*;
+use bevy_rapier2d::prelude::*;
+use std::fmt::Write;
+
+const HUD_TOGGLE_KEY: KeyCode = KeyCode::F1;
+
+#[derive(Component)]
+pub struct HudRoot;
+
+#[derive(Component)]
+pub struct HudText;
+
+pub fn setup_hud(mut commands: Commands, font: Res<GameFont>) {
+    commands
+        .spawn((
+            HudRoot,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.0),
+                        top: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                HudText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: 20.0,
+                        color: ZOOP_BLACK,
+                    },
+                ),
+            ));
+        });
+}
+
+pub fn toggle_hud(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut root_query: Query<&mut Visibility, With<HudRoot>>,
+) {
+    if !keyboard_input.just_pressed(HUD_TOGGLE_KEY) {
+        return;
+    }
+    for mut visibility in root_query.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+pub fn format_race_time(frames: i32, fps: i32) -> String {
+    let seconds = frames.max(0) as f32 / fps as f32;
+    format!("{:02}:{:05.2}", (seconds / 60.0) as u32, seconds % 60.0)
+}
+
+pub fn update_hud(
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
+    enable_physics_after: Res<EnablePhysicsAfter>,
+    state: Res<GameState>,
+    session: Option<ResMut<Session<GGRSConfig>>>,
+    handshake: Option<Res<TuningHandshake>>,
+    car_query: Query<(&Velocity, &Player), With<CarMeta>>,
+    mut text_query: Query<&mut Text, With<HudText>>,
+) {
+    let fps = config.fps as i32;
+    let mut hud = String::new();
+
+    // Local car, or the host's car when spectating
+    let followed_handle = config.followed_handle();
+    let standings = state.standings();
+    let local_car = standings
+        .iter()
+        .enumerate()
+        .find(|(_, car)| car.player.handle == followed_handle);
+    if let Some((position, car)) = local_car {
+        let speed = car_query
+            .iter()
+            .find(|(_, player)| player.handle == car.player.handle)
+            .map(|(velocity, _)| velocity.linvel.length() / config.pixels_per_meter)
+            .unwrap_or(0.0);
+        let lap = (car.progress.laps + 1).min(config.track.laps);
+        let race_frames = if car.progress.finished {
+            car.progress.finished_frame
+        } else {
+            current_frame.0
+        } - enable_physics_after.end;
+
+        let _ = writeln!(hud, "Speed {:>4.0} km/h", speed * 3.6);
+        let _ = writeln!(hud, "Lap   {}/{}", lap, config.track.laps);
+        let _ = writeln!(hud, "Pos   {}/{}", position + 1, standings.len());
+        let _ = writeln!(hud, "Time  {}", format_race_time(race_frames, fps));
+        let _ = writeln!(
+            hud,
+            "Dmg   {:>3.0}% {} hits",
+            car.damage.amount * 100.0,
+            car.damage.hits
+        );
+    }
+
+    // Peers which disagree on the handling never start the match
+    if let Some(handshake) = handshake {
+        if let Some(mismatch) = &handshake.mismatch {
+            let _ = writeln!(hud, "{}", mismatch);
+        } else if !handshake.started {
+            let _ = writeln!(hud, "Waiting for the other players");
+        }
+    }
+
+    // Match between rounds
+    let match_state = &state.match_state;
+    if config.best_of > 1 {
+        let wins = match_state.wins.get(followed_handle).copied().unwrap_or(0);
+        let _ = writeln!(
+            hud,
+            "Round {} of best of {}, {} won",
+            match_state.round, config.best_of, wins
+        );
+    }
+    if state.race.is_over {
+        if let Some(winner) = match_state.winner(config.best_of) {
+            let _ = writeln!(hud, "P{} won the match", winner);
+        }
+        let _ = writeln!(
+            hud,
+            "Press {:?} for a rematch ({}/{})",
+            config.key_bindings.rematch,
+            state.race.rematch_votes.len(),
+            config.players.len()
+        );
+    }
+
+    // Remote players
+    if let Some(mut session) = session {
+        if let Session::P2PSession(s) = session.as_mut() {
+            for (handle, player) in config.players.iter().enumerate() {
+                if player.is_local {
+                    continue;
+                }
+                match s.network_stats(handle) {
+                    Ok(stats) => {
+                        let _ = writeln!(
+                            hud,
+                            "P{} ping {}ms behind {}/{} queue {}",
+                            handle,
+                            stats.ping,
+                            stats.local_frames_behind,
+                            stats.remote_frames_behind,
+                            stats.send_queue_len
+                        );
+                    }
+                    Err(_) => {
+                        let _ = writeln!(hud, "P{} waiting for stats", handle);
+                    }
+                }
+            }
+        }
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+}
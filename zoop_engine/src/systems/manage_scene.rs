@@ -1,41 +1,97 @@
 use crate::domain::car::spawn_car;
 
-use crate::domain::colors::{ZOOP_BLACK, ZOOP_RED};
+use crate::domain::car_body::CarMeta;
+use crate::domain::car_class::{car_color, CarSpec};
+use crate::domain::colors::{ZOOP_BLACK, ZOOP_DARK_BLUE, ZOOP_DARK_RED};
 use crate::domain::game_config::GameConfig;
-use crate::domain::game_state::{GameCar, GameEntity, GameState};
+use crate::domain::game_state::{
+    EntityPhysics, GameCar, GameCheckpoint, GameEntity, GameGround, GameState, GameTire, GameWall,
+};
 use crate::domain::player::Player;
-use crate::domain::spawn::DeterministicSpawn;
+use crate::domain::race::{MatchState, RaceState};
+use crate::domain::spawn::{DeterministicSpawn, SpawnError, SpawnPool};
+use crate::domain::surface::{surface_color, SurfaceKind};
+use crate::domain::tire::{TireMeta, TirePhysics};
+use crate::domain::track::TrackWallKind;
+use crate::domain::track_body::{CheckpointBody, GroundBody, WallBody};
 
+use bevy::app::AppExit;
 use bevy::prelude::*;
 use bevy_ggrs::RollbackIdProvider;
 use bevy_rapier2d::prelude::*;
 
 pub fn init_scene(config: &GameConfig) -> GameState {
     println!("Initiating scene state");
-    let cars = config
-        .players
-        .iter()
-        .enumerate()
-        .map(|(handle, _)| {
-            let player = Player { handle };
-            let position = Vec3 {
-                x: config.car_half_size().x * 6.0 * (handle as f32),
-                y: 0.0,
-                z: 0.0,
-            };
-            let tire_half_size = config.tire_half_size();
-            let car_half_size = config.car_half_size();
+    let ppm = config.pixels_per_meter;
+    let track = &config.track;
+
+    // Track entities are spawned before cars, always in file order
+    let grounds = track.areas.iter().map(|area| {
+        GameEntity::Ground(GameGround {
+            surface: SurfaceKind::Asphalt,
+            // Slightly behind everything else, but still in front of the 2D camera's far plane
+            transform: area.transform(ppm, -0.05),
+            half_size: area.half_size(ppm),
+        })
+    });
+
+    // Drawn over the asphalt, in file order so that later regions cover earlier ones
+    let surfaces = track.surfaces.iter().enumerate().map(|(index, surface)| {
+        GameEntity::Ground(GameGround {
+            surface: surface.kind,
+            transform: surface.rect.transform(ppm, -0.04 + index as f32 * 0.0001),
+            half_size: surface.rect.half_size(ppm),
+        })
+    });
 
-            GameEntity::Car(GameCar::fixed_for_player(
-                player,
-                position,
-                car_half_size,
-                tire_half_size,
-            ))
+    let walls = track.walls.iter().map(|wall| {
+        GameEntity::Wall(GameWall {
+            kind: wall.kind,
+            transform: wall.rect.transform(ppm, 0.0),
+            half_size: wall.rect.half_size(ppm),
         })
-        .collect();
+    });
+
+    let finish = std::iter::once(GameEntity::Checkpoint(GameCheckpoint {
+        index: 0,
+        is_finish: true,
+        transform: track.finish.transform(ppm, -0.01),
+        half_size: track.finish.half_size(ppm),
+    }));
+
+    let checkpoints = track.checkpoints.iter().enumerate().map(|(index, rect)| {
+        GameEntity::Checkpoint(GameCheckpoint {
+            index: index as u16,
+            is_finish: false,
+            transform: rect.transform(ppm, -0.01),
+            half_size: rect.half_size(ppm),
+        })
+    });
+
+    let cars = config.players.iter().enumerate().map(|(handle, _)| {
+        let player = Player { handle };
+        let (grid_position, angle) = track.start.grid_slot(handle);
+        let position = Vec3 {
+            x: grid_position.x * ppm,
+            y: grid_position.y * ppm,
+            z: 0.0,
+        };
+        let spec = CarSpec::of(config.car_class(handle), config);
+
+        GameEntity::Car(GameCar::fixed_for_player(player, position, angle, spec))
+    });
 
-    GameState { entities: cars }
+    GameState {
+        entities: grounds
+            .chain(surfaces)
+            .chain(walls)
+            .chain(finish)
+            .chain(checkpoints)
+            .chain(cars)
+            .collect(),
+        race: RaceState::default(),
+        match_state: MatchState::new(config.players.len()),
+    }
 }
 
 pub fn destroy_scene(
@@ -65,224 +121,247 @@ pub fn setup_scene(
     mut rip: ResMut<RollbackIdProvider>,
     spawn_pool: Query<(Entity, &DeterministicSpawn)>,
     mut commands: Commands,
+    mut exit: EventWriter<AppExit>,
 ) {
-    // Get our entities and sort them by the spawn component index
-    let mut sorted_spawn_pool: Vec<(Entity, &DeterministicSpawn)> = spawn_pool.iter().collect();
-    sorted_spawn_pool.sort_by_key(|e| e.1.index);
-    // Get the Entities in reverse for easy popping
-    let mut sorted_entity_pool: Vec<Entity> = sorted_spawn_pool.iter().map(|p| p.0).rev().collect();
+    let mut spawn_pool = SpawnPool::new(spawn_pool.iter());
 
-    spawn_scene(
+    if let Err(error) = spawn_scene(
         config.as_ref(),
         &state,
         &mut commands,
-        &mut sorted_entity_pool,
+        &mut spawn_pool,
         &mut rip,
-    );
+    ) {
+        error!("Failed to spawn the scene: {}", error);
+        exit.send(AppExit);
+    }
 }
 
 pub fn spawn_scene(
     config: &GameConfig,
     state: &GameState,
     commands: &mut Commands,
-    spawn_pool: &mut Vec<Entity>,
+    spawn_pool: &mut SpawnPool,
     rip: &mut RollbackIdProvider,
-) {
+) -> Result<(), SpawnError> {
     println!("Spawning scene from state");
-    for entity in state.entities.iter() {
+    for (index, entity) in state.entities.iter().enumerate() {
         match entity {
             GameEntity::Stub() => (),
             GameEntity::Car(car) => {
                 println!("Spawning car for player {}", car.player.handle);
-                setup_car(config, car.clone(), commands, spawn_pool, rip)
+                setup_car(config, car.clone(), commands, spawn_pool, rip)?
+            }
+            GameEntity::Wall(wall) => {
+                setup_wall(config, index, wall.clone(), commands, spawn_pool)?
+            }
+            GameEntity::Ground(ground) => {
+                setup_ground(index, ground.clone(), commands, spawn_pool)?
+            }
+            GameEntity::Checkpoint(checkpoint) => {
+                setup_checkpoint(index, checkpoint.clone(), commands, spawn_pool)?
             }
         }
     }
-    while !spawn_pool.is_empty() {
-        let mut leftover = commands.entity(spawn_pool.pop().unwrap());
-        leftover.despawn();
+    for leftover in spawn_pool.leftovers() {
+        commands.entity(leftover).despawn();
+    }
+    Ok(())
+}
+
+pub fn store_car(
+    car_query: &Query<
+        (
+            &Transform,
+            &Velocity,
+            &ExternalForce,
+            &ExternalImpulse,
+            &ReadMassProperties,
+            &Player,
+        ),
+        (With<CarMeta>, Without<TireMeta>),
+    >,
+    fallback: EntityPhysics,
+    player_handle: usize,
+) -> EntityPhysics {
+    // Store car from ECS query into game state
+    car_query
+        .iter()
+        .find(|(_, _, _, _, _, player)| player.handle == player_handle)
+        .map(|(transform, velocity, force, impulse, mass, _)| {
+            EntityPhysics::of(*transform, *velocity, *force, *impulse, *mass)
+        })
+        .unwrap_or(fallback)
+}
+
+pub fn store_tire(
+    tire_query: &Query<
+        (
+            &Transform,
+            &Velocity,
+            &ExternalForce,
+            &ExternalImpulse,
+            &ReadMassProperties,
+            &TirePhysics,
+            &TireMeta,
+            &Player,
+        ),
+        Without<CarMeta>,
+    >,
+    is_front: bool,
+    is_right: bool,
+    fallback: GameTire,
+    player_handle: usize,
+) -> GameTire {
+    // Store tire from ECS query into game state
+    tire_query
+        .iter()
+        .find(|(_, _, _, _, _, _, meta, player)| {
+            player.handle == player_handle && meta.is_front == is_front && meta.is_right == is_right
+        })
+        .map(
+            |(transform, velocity, force, impulse, mass, physics, _, _)| {
+                GameTire::of(
+                    *transform,
+                    *velocity,
+                    *force,
+                    *impulse,
+                    *mass,
+                    physics.angle,
+                )
+            },
+        )
+        .unwrap_or(fallback)
+}
+
+/// Copies the live car and tire state into `GameState` after every step,
+/// so that it describes the scene without relying on Rapier internals
+pub fn store_scene(
+    car_query: Query<
+        (
+            &Transform,
+            &Velocity,
+            &ExternalForce,
+            &ExternalImpulse,
+            &ReadMassProperties,
+            &Player,
+        ),
+        (With<CarMeta>, Without<TireMeta>),
+    >,
+    tire_query: Query<
+        (
+            &Transform,
+            &Velocity,
+            &ExternalForce,
+            &ExternalImpulse,
+            &ReadMassProperties,
+            &TirePhysics,
+            &TireMeta,
+            &Player,
+        ),
+        Without<CarMeta>,
+    >,
+    mut state: ResMut<GameState>,
+) {
+    for car in state.cars_mut() {
+        let handle = car.player.handle;
+        car.physics = store_car(&car_query, car.physics.clone(), handle);
+        car.tire_top_right =
+            store_tire(&tire_query, true, true, car.tire_top_right.clone(), handle);
+        car.tire_top_left = store_tire(&tire_query, true, false, car.tire_top_left.clone(), handle);
+        car.tire_bottom_right = store_tire(
+            &tire_query,
+            false,
+            true,
+            car.tire_bottom_right.clone(),
+            handle,
+        );
+        car.tire_bottom_left = store_tire(
+            &tire_query,
+            false,
+            false,
+            car.tire_bottom_left.clone(),
+            handle,
+        );
     }
 }
-//
-// pub fn store_car(
-//     car_query: &mut Query<
-//         (
-//             &Transform,
-//             &Velocity,
-//             &ExternalForce,
-//             &ExternalImpulse,
-//             &ReadMassProperties,
-//             &Player,
-//         ),
-//         Without<TireMeta>,
-//     >,
-//     fallback: EntityPhysics,
-//     player_handle: usize,
-// ) -> EntityPhysics {
-//     // Sort query for more determinism
-//     let mut query = car_query.iter_mut().collect::<Vec<_>>();
-//     query.sort_by_key(|(_, _, _, _, _, player)| player.handle);
-//
-//     // Store tire from ECS query into game state
-//     query
-//         .into_iter()
-//         .find(|(_, _, _, _, _, player)| player.handle == player_handle)
-//         .map(|(transform, velocity, force, impulse, mass, _)| {
-//             EntityPhysics::of(
-//                 transform.clone(),
-//                 velocity.clone(),
-//                 force.clone(),
-//                 impulse.clone(),
-//                 mass.clone(),
-//             )
-//         })
-//         .unwrap_or_else(|| fallback)
-// }
-//
-// pub fn store_tire(
-//     tire_query: &mut Query<
-//         (
-//             &Transform,
-//             &Velocity,
-//             &ExternalForce,
-//             &ExternalImpulse,
-//             &ReadMassProperties,
-//             &TirePhysics,
-//             &TireMeta,
-//             &Player,
-//         ),
-//         Without<CarMeta>,
-//     >,
-//     is_front: bool,
-//     is_right: bool,
-//     fallback: GameTire,
-//     player_handle: usize,
-// ) -> GameTire {
-//     // Sort query for more determinism
-//     let mut query = tire_query.iter_mut().collect::<Vec<_>>();
-//     query.sort_by_key(|(_, _, _, _, _, _, meta, player)| {
-//         (
-//             player.handle,
-//             if meta.is_front { 1 } else { 0 },
-//             if meta.is_right { 1 } else { 0 },
-//         )
-//     });
-//
-//     // Store tire from ECS query into game state
-//     query
-//         .into_iter()
-//         .find(|(_, _, _, _, _, _, meta, player)| {
-//             player.handle == player_handle && meta.is_front == is_front && meta.is_right == is_right
-//         })
-//         .map(
-//             |(transform, velocity, force, impulse, mass, physics, _, _)| {
-//                 GameTire::of(
-//                     transform.clone(),
-//                     velocity.clone(),
-//                     force.clone(),
-//                     impulse.clone(),
-//                     mass.clone(),
-//                     physics.angle,
-//                 )
-//             },
-//         )
-//         .unwrap_or_else(|| fallback)
-// }
-//
-// pub fn store_scene(
-//     mut car_query: Query<
-//         (
-//             &Transform,
-//             &Velocity,
-//             &ExternalForce,
-//             &ExternalImpulse,
-//             &ReadMassProperties,
-//             &Player,
-//         ),
-//         Without<TireMeta>,
-//     >,
-//     mut tire_query: Query<
-//         (
-//             &Transform,
-//             &Velocity,
-//             &ExternalForce,
-//             &ExternalImpulse,
-//             &ReadMassProperties,
-//             &TirePhysics,
-//             &TireMeta,
-//             &Player,
-//         ),
-//         Without<CarMeta>,
-//     >,
-//     mut state: ResMut<GameState>,
-// ) {
-//     // println!("Storing state from scene");
-//     let new_entities = state
-//         .entities
-//         .iter_mut()
-//         .map(|e| match e {
-//             GameEntity::Stub() => GameEntity::Stub(),
-//             GameEntity::Car(c) => {
-//                 // Take existing car
-//                 let mut new_car = c.clone();
-//
-//                 // Copy car
-//                 new_car.physics = store_car(&mut car_query, new_car.physics, c.player.handle);
-//                 new_car.tire_top_right = store_tire(
-//                     &mut tire_query,
-//                     true,
-//                     true,
-//                     new_car.tire_top_right.clone(),
-//                     c.player.handle,
-//                 );
-//                 new_car.tire_top_left = store_tire(
-//                     &mut tire_query,
-//                     true,
-//                     false,
-//                     new_car.tire_top_left.clone(),
-//                     c.player.handle,
-//                 );
-//                 new_car.tire_bottom_right = store_tire(
-//                     &mut tire_query,
-//                     false,
-//                     true,
-//                     new_car.tire_bottom_right.clone(),
-//                     c.player.handle,
-//                 );
-//                 new_car.tire_bottom_left = store_tire(
-//                     &mut tire_query,
-//                     false,
-//                     false,
-//                     new_car.tire_bottom_left.clone(),
-//                     c.player.handle,
-//                 );
-//
-//                 // Store new car
-//                 GameEntity::Car(new_car)
-//             }
-//         })
-//         .collect();
-//     state.entities = new_entities;
-// }
 
 pub fn setup_car(
     config: &GameConfig,
     car: GameCar,
     commands: &mut Commands,
-    spawn_pool: &mut Vec<Entity>,
+    spawn_pool: &mut SpawnPool,
     rip: &mut RollbackIdProvider,
-) {
+) -> Result<(), SpawnError> {
     spawn_car(
         commands,
         spawn_pool,
         rip,
         car.player.clone(),
-        String::from(format!("Car #{}", car.player.handle)),
-        config.car_half_size(),
-        config.tire_half_size(),
-        ZOOP_RED,
+        String::from(format!(
+            "{} #{}",
+            config.car_class(car.player.handle),
+            car.player.handle
+        )),
+        car.spec.half_size,
+        car.spec.tire_half_size,
+        car_color(config.car_class(car.player.handle)),
         ZOOP_BLACK,
         config.tire_damping(),
+        config.collision_force_threshold,
         car,
-    );
+    )
+}
+
+pub fn setup_wall(
+    config: &GameConfig,
+    index: usize,
+    wall: GameWall,
+    commands: &mut Commands,
+    spawn_pool: &mut SpawnPool,
+) -> Result<(), SpawnError> {
+    let (title, color) = match wall.kind {
+        TrackWallKind::Wall => (format!("Wall #{}", index), ZOOP_DARK_BLUE),
+        TrackWallKind::Barrier => (format!("Barrier #{}", index), ZOOP_DARK_RED),
+    };
+    let mut entity = commands.entity(spawn_pool.pop()?);
+    entity.insert(WallBody::build(
+        title,
+        color,
+        config.collision_force_threshold,
+        wall,
+    ));
+    Ok(())
+}
+
+pub fn setup_ground(
+    index: usize,
+    ground: GameGround,
+    commands: &mut Commands,
+    spawn_pool: &mut SpawnPool,
+) -> Result<(), SpawnError> {
+    let mut entity = commands.entity(spawn_pool.pop()?);
+    let title = match ground.surface {
+        SurfaceKind::Asphalt => format!("Ground #{}", index),
+        surface => format!("Ground {:?} #{}", surface, index),
+    };
+    let color = surface_color(ground.surface);
+    entity.insert(GroundBody::build(title, color, ground));
+    Ok(())
+}
+
+pub fn setup_checkpoint(
+    index: usize,
+    checkpoint: GameCheckpoint,
+    commands: &mut Commands,
+    spawn_pool: &mut SpawnPool,
+) -> Result<(), SpawnError> {
+    let title = if checkpoint.is_finish {
+        format!("Finish #{}", index)
+    } else {
+        format!("Checkpoint {} #{}", checkpoint.index, index)
+    };
+    let mut entity = commands.entity(spawn_pool.pop()?);
+    entity.insert(CheckpointBody::build(title, ZOOP_BLACK, checkpoint));
+    Ok(())
 }
@@ -0,0 +1,132 @@
+use crate::domain::car_body::CarMeta;
+use crate::domain::frames::*;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_state::GameState;
+use crate::domain::player::Player;
+use crate::domain::track_body::CheckpointMeta;
+use crate::services::race_result::publish_race_result;
+use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use zoop_shared::race_result::{RaceResult, RaceStanding};
+
+pub fn update_race_progress(
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
+    rapier: Res<RapierContext>,
+    car_query: Query<(Entity, &Player), With<CarMeta>>,
+    checkpoint_query: Query<(Entity, &CheckpointMeta)>,
+    mut state: ResMut<GameState>,
+) {
+    // Results are final once the race is over
+    if state.race.is_over {
+        return;
+    }
+
+    let frame = current_frame.0;
+    let checkpoint_count = config.track.checkpoints.len() as u16;
+    let finish = checkpoint_query
+        .iter()
+        .find(|(_, meta)| meta.is_finish)
+        .map(|(entity, _)| entity);
+    let checkpoint = |index: u16| {
+        checkpoint_query
+            .iter()
+            .find(|(_, meta)| !meta.is_finish && meta.index == index)
+            .map(|(entity, _)| entity)
+    };
+    // The narrow phase is part of the rolled back Rapier context, so this is safe to query
+    let touches = |car: Entity, sensor: Option<Entity>| {
+        sensor
+            .and_then(|sensor| rapier.intersection_pair(car, sensor))
+            .unwrap_or(false)
+    };
+
+    // Cars are stored in player handle order, so simultaneous finishes are ordered deterministically
+    let mut newly_finished = Vec::new();
+    for car in state.cars_mut() {
+        let Some((car_entity, _)) = car_query
+            .iter()
+            .find(|(_, player)| player.handle == car.player.handle)
+        else {
+            continue;
+        };
+        let was_finished = car.progress.finished;
+        car.progress.advance(
+            |index| touches(car_entity, checkpoint(index)),
+            touches(car_entity, finish),
+            checkpoint_count,
+            config.track.laps,
+            frame,
+        );
+        if !was_finished && car.progress.finished {
+            info!("Player {} finished on frame {}", car.player.handle, frame);
+            newly_finished.push(car.player.handle);
+        }
+    }
+    state.race.finish_order.extend(newly_finished);
+
+    // The race is over when everyone finished or the stragglers ran out of time
+    let car_count = state.cars().count();
+    let first_finished_frame = state.race.finish_order.first().and_then(|handle| {
+        state
+            .cars()
+            .find(|car| car.player.handle == *handle)
+            .map(|car| car.progress.finished_frame)
+    });
+    let grace_frames = config.race_finish_grace_seconds as i32 * config.fps as i32;
+    let grace_over = first_finished_frame
+        .map(|finished_frame| frame >= finished_frame + grace_frames)
+        .unwrap_or(false);
+    if state.race.finish_order.len() == car_count || grace_over {
+        info!("Race over on frame {}", frame);
+        state.race.is_over = true;
+        state.race.over_frame = frame;
+        if let Some(winner) = state.race.finish_order.first().copied() {
+            state.match_state.record_win(winner);
+        }
+    }
+}
+
+pub fn race_result(config: &GameConfig, state: &GameState, start_frame: ggrs::Frame) -> RaceResult {
+    let standings = state
+        .standings()
+        .into_iter()
+        .enumerate()
+        .map(|(i, car)| RaceStanding {
+            player: config.players[car.player.handle].id.clone(),
+            position: (i + 1) as u32,
+            laps: car.progress.laps as u32,
+            finished: car.progress.finished,
+            race_seconds: if car.progress.finished {
+                Some((car.progress.finished_frame - start_frame) as f32 / config.fps as f32)
+            } else {
+                None
+            },
+        })
+        .collect();
+
+    RaceResult { standings }
+}
+
+/// Hands out the result once the end of the race can't be rolled back anymore
+pub fn publish_race_result_when_confirmed(
+    config: Res<GameConfig>,
+    state: Res<GameState>,
+    confirmed_frame: Res<ConfirmedFrame>,
+    enable_physics_after: Res<EnablePhysicsAfter>,
+    mut published_round: Local<Option<u16>>,
+) {
+    let round = state.match_state.round;
+    if *published_round == Some(round)
+        || !state.race.is_over
+        || state.race.over_frame > confirmed_frame.0
+    {
+        return;
+    }
+
+    let result = race_result(config.as_ref(), state.as_ref(), enable_physics_after.end);
+    info!("Race result {:?}", result);
+    publish_race_result(result);
+    *published_round = Some(round);
+}
@@ -0,0 +1,111 @@
+use crate::domain::colors::*;
+use crate::domain::frame_inputs::FrameInputs;
+use crate::domain::frames::*;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_font::GameFont;
+use crate::domain::game_state::GameState;
+use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct CountdownText;
+
+/// Marks players who pressed throttle before the start signal
+pub fn detect_false_starts(
+    config: Res<GameConfig>,
+    inputs: Res<FrameInputs>,
+    current_frame: Res<CurrentFrame>,
+    enable_physics_after: Res<EnablePhysicsAfter>,
+    mut state: ResMut<GameState>,
+) {
+    let countdown = enable_physics_after.countdown(
+        current_frame.0,
+        config.fps as i32,
+        config.countdown_seconds as i32,
+    );
+    if countdown.is_none() {
+        return;
+    }
+
+    for car in state.cars_mut() {
+        let (controls, _) = inputs.0[car.player.handle];
+        if controls.accelerating() && !car.progress.false_start {
+            info!(
+                "Player {} false started on frame {}",
+                car.player.handle, current_frame.0
+            );
+            car.progress.false_start = true;
+        }
+    }
+}
+
+pub fn setup_countdown(mut commands: Commands, font: Res<GameFont>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                CountdownText,
+                TextBundle {
+                    visibility: Visibility::Hidden,
+                    ..TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: font.0.clone(),
+                            font_size: 120.0,
+                            color: ZOOP_DARK_RED,
+                        },
+                    )
+                },
+            ));
+        });
+}
+
+/// Shows 3-2-1-GO from the synchronized frame, not from wall clock time
+pub fn update_countdown(
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
+    enable_physics_after: Res<EnablePhysicsAfter>,
+    state: Res<GameState>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<CountdownText>>,
+) {
+    let fps = config.fps as i32;
+    let frame = current_frame.0;
+    let start_frame = enable_physics_after.end;
+    let penalty_frames = config.false_start_penalty_seconds as i32 * fps;
+    let followed_handle = config.followed_handle();
+    let local_false_start = state
+        .cars()
+        .find(|car| car.player.handle == followed_handle)
+        .map(|car| car.progress.is_held(frame, start_frame, penalty_frames))
+        .unwrap_or(false);
+
+    let message = if let Some(seconds) =
+        enable_physics_after.countdown(frame, fps, config.countdown_seconds as i32)
+    {
+        Some(seconds.to_string())
+    } else if start_frame <= frame && local_false_start {
+        Some("FALSE START".to_string())
+    } else if start_frame <= frame && frame < start_frame + fps {
+        Some("GO!".to_string())
+    } else {
+        None
+    };
+
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        match &message {
+            Some(message) => {
+                text.sections[0].value = message.clone();
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
@@ -1,14 +1,21 @@
 use crate::domain::controls::Controls;
 use crate::domain::desync::*;
 use crate::domain::frames::*;
-use crate::systems::rollback_rapier_context::PhysicsEnabled;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_mode::GameMode;
+use crate::domain::key_bindings::KeyBindings;
 use bevy::prelude::*;
 use ggrs::PlayerHandle;
 
 pub fn read_controls(
-    _handle: In<PlayerHandle>,
+    handle: In<PlayerHandle>,
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
     keyboard_input: Res<Input<KeyCode>>,
-    physics_enabled: Res<PhysicsEnabled>,
+    key_bindings: Res<KeyBindings>,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
     mut hashes: ResMut<FrameHashes>,
     validatable_frame: Res<ValidatableFrame>,
 ) -> Controls {
@@ -35,13 +42,36 @@ pub fn read_controls(
         }
     }
 
-    if !physics_enabled.0 {
-        Controls::empty(last_confirmed_hash, last_confirmed_frame)
-    } else {
-        Controls::from_wasd(
-            keyboard_input.as_ref(),
+    // Sync tests can drive players without any input devices
+    if let GameMode::SyncTest(sync_test) = &config.mode {
+        if let Some(controls) = sync_test.inputs.controls(
+            handle.0,
+            current_frame.0 + 1,
             last_confirmed_hash,
             last_confirmed_frame,
-        )
+        ) {
+            return controls;
+        }
+    }
+
+    // Controls are sent during the countdown as well, the simulation
+    // decides what to ignore, so that false starts can be detected
+    let keyboard = Controls::from_bindings(
+        keyboard_input.as_ref(),
+        key_bindings.as_ref(),
+        last_confirmed_hash,
+        last_confirmed_frame,
+    );
+
+    // The first connected gamepad drives alongside the keyboard
+    match gamepads.iter().min_by_key(|gamepad| gamepad.id) {
+        Some(gamepad) => keyboard.merge(Controls::from_gamepad(
+            gamepad,
+            gamepad_axes.as_ref(),
+            gamepad_button_axes.as_ref(),
+            last_confirmed_hash,
+            last_confirmed_frame,
+        )),
+        None => keyboard,
     }
 }
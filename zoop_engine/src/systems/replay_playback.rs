@@ -0,0 +1,259 @@
+use crate::domain::colors::*;
+use crate::domain::frame_inputs::FrameInputs;
+use crate::domain::frames::CurrentFrame;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_font::GameFont;
+use crate::domain::game_state::GameState;
+use crate::domain::input_stream::InputStream;
+use crate::domain::rapier_rollback_state::{PhysicsStateBuffers, RapierRollbackState};
+use crate::domain::tire::TirePhysics;
+use crate::systems::hud::format_race_time;
+use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
+use bevy::prelude::*;
+use bevy_ggrs::{GGRSSchedule, Rollback};
+use bevy_rapier2d::prelude::*;
+use ggrs::Frame;
+
+const PAUSE_KEY: KeyCode = KeyCode::Space;
+const SEEK_BACK_KEY: KeyCode = KeyCode::Left;
+const SEEK_FORWARD_KEY: KeyCode = KeyCode::Right;
+const SLOWER_KEY: KeyCode = KeyCode::Down;
+const FASTER_KEY: KeyCode = KeyCode::Up;
+const RESTART_KEY: KeyCode = KeyCode::Home;
+const SEEK_SECONDS: i32 = 5;
+const MAX_SPEED: u32 = 8;
+
+/// Simulates one frame of recorded inputs, the schedule advances `CurrentFrame` itself
+pub fn step_recorded_frame(world: &mut World, inputs: &InputStream) {
+    let frame = world.resource::<CurrentFrame>().0 + 1;
+    world.insert_resource(FrameInputs::confirmed(inputs.controls(frame)));
+    world.run_schedule(GGRSSchedule);
+}
+
+/// Values of one component for every rollback entity
+struct ComponentSnapshot<C: Component + Clone>(Vec<(Entity, C)>);
+
+impl<C: Component + Clone> ComponentSnapshot<C> {
+    fn save(world: &mut World) -> Self {
+        let mut query = world.query_filtered::<(Entity, &C), With<Rollback>>();
+        Self(
+            query
+                .iter(world)
+                .map(|(entity, component)| (entity, component.clone()))
+                .collect(),
+        )
+    }
+
+    fn restore(&self, world: &mut World) {
+        for (entity, component) in self.0.iter() {
+            if let Some(mut entity) = world.get_entity_mut(*entity) {
+                entity.insert(component.clone());
+            }
+        }
+    }
+}
+
+/// The rolled back state of the first frame, seeking backwards resimulates from here.
+/// Restoring it is a rollback like any other, `rollback_rapier_context` restores Rapier from it.
+pub struct ReplaySnapshot {
+    rapier_state: RapierRollbackState,
+    rapier_bytes: Vec<u8>,
+    current_frame: CurrentFrame,
+    enable_physics_after: EnablePhysicsAfter,
+    game_state: GameState,
+    global_transforms: ComponentSnapshot<GlobalTransform>,
+    transforms: ComponentSnapshot<Transform>,
+    velocities: ComponentSnapshot<Velocity>,
+    sleeping: ComponentSnapshot<Sleeping>,
+    tires: ComponentSnapshot<TirePhysics>,
+    forces: ComponentSnapshot<ExternalForce>,
+    impulses: ComponentSnapshot<ExternalImpulse>,
+}
+
+impl ReplaySnapshot {
+    fn save(world: &mut World) -> Self {
+        let rapier_state = *world.resource::<RapierRollbackState>();
+        ReplaySnapshot {
+            rapier_state,
+            rapier_bytes: world
+                .resource::<PhysicsStateBuffers>()
+                .get(rapier_state.frame)
+                .map(<[u8]>::to_vec)
+                .unwrap_or_default(),
+            current_frame: *world.resource::<CurrentFrame>(),
+            enable_physics_after: *world.resource::<EnablePhysicsAfter>(),
+            game_state: world.resource::<GameState>().clone(),
+            global_transforms: ComponentSnapshot::save(world),
+            transforms: ComponentSnapshot::save(world),
+            velocities: ComponentSnapshot::save(world),
+            sleeping: ComponentSnapshot::save(world),
+            tires: ComponentSnapshot::save(world),
+            forces: ComponentSnapshot::save(world),
+            impulses: ComponentSnapshot::save(world),
+        }
+    }
+
+    fn restore(&self, world: &mut World) {
+        world
+            .resource_mut::<PhysicsStateBuffers>()
+            .insert(self.rapier_state.frame, &self.rapier_bytes);
+        world.insert_resource(self.rapier_state);
+        world.insert_resource(self.current_frame);
+        world.insert_resource(self.enable_physics_after);
+        world.insert_resource(self.game_state.clone());
+        self.global_transforms.restore(world);
+        self.transforms.restore(world);
+        self.velocities.restore(world);
+        self.sleeping.restore(world);
+        self.tires.restore(world);
+        self.forces.restore(world);
+        self.impulses.restore(world);
+    }
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub inputs: InputStream,
+    pub paused: bool,
+    pub speed: u32,
+    pub seek_to: Option<Frame>,
+    /// Playback time not yet simulated, in seconds
+    pub elapsed: f32,
+    pub start: Option<ReplaySnapshot>,
+}
+
+impl ReplayPlayback {
+    pub fn new(inputs: InputStream) -> ReplayPlayback {
+        ReplayPlayback {
+            inputs,
+            paused: false,
+            speed: 1,
+            seek_to: None,
+            elapsed: 0.0,
+            start: None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ReplayText;
+
+pub fn setup_replay_overlay(mut commands: Commands, font: Res<GameFont>) {
+    commands.spawn((
+        ReplayText,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: font.0.clone(),
+                font_size: 20.0,
+                color: ZOOP_BLACK,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        }),
+    ));
+}
+
+pub fn control_replay(
+    config: Res<GameConfig>,
+    keyboard_input: Res<Input<KeyCode>>,
+    current_frame: Res<CurrentFrame>,
+    mut playback: ResMut<ReplayPlayback>,
+) {
+    let seek_frames = SEEK_SECONDS * config.fps as i32;
+    let from_frame = playback.seek_to.unwrap_or(current_frame.0);
+
+    if keyboard_input.just_pressed(PAUSE_KEY) {
+        playback.paused = !playback.paused;
+    }
+    if keyboard_input.just_pressed(SEEK_BACK_KEY) {
+        playback.seek_to = Some(from_frame - seek_frames);
+    }
+    if keyboard_input.just_pressed(SEEK_FORWARD_KEY) {
+        playback.seek_to = Some(from_frame + seek_frames);
+    }
+    if keyboard_input.just_pressed(RESTART_KEY) {
+        playback.seek_to = Some(1);
+    }
+    if keyboard_input.just_pressed(FASTER_KEY) {
+        playback.speed = (playback.speed * 2).min(MAX_SPEED);
+    }
+    if keyboard_input.just_pressed(SLOWER_KEY) {
+        playback.speed = (playback.speed / 2).max(1);
+    }
+}
+
+/// Steps the simulation through the replay at playback speed, instead of GGRS
+pub fn play_replay(world: &mut World) {
+    let delta = world.resource::<Time>().delta_seconds();
+    let frame_seconds = 1.0 / world.resource::<GameConfig>().fps as f32;
+    let current_frame = |world: &World| world.resource::<CurrentFrame>().0;
+
+    world.resource_scope(|world, mut playback: Mut<ReplayPlayback>| {
+        let last_frame = playback.inputs.frame_count();
+
+        // The first frame is where every seek backwards starts resimulating from
+        if playback.start.is_none() {
+            step_recorded_frame(world, &playback.inputs);
+            playback.start = Some(ReplaySnapshot::save(world));
+        }
+
+        if let Some(target) = playback.seek_to.take() {
+            let target = target.clamp(1, last_frame.max(1));
+            if target < current_frame(world) {
+                if let Some(start) = playback.start.as_ref() {
+                    start.restore(world);
+                }
+            }
+            while current_frame(world) < target {
+                step_recorded_frame(world, &playback.inputs);
+            }
+            playback.elapsed = 0.0;
+        }
+
+        if playback.paused {
+            return;
+        }
+        playback.elapsed += delta * playback.speed as f32;
+        while playback.elapsed >= frame_seconds {
+            if current_frame(world) >= last_frame {
+                playback.paused = true;
+                playback.elapsed = 0.0;
+                break;
+            }
+            playback.elapsed -= frame_seconds;
+            step_recorded_frame(world, &playback.inputs);
+        }
+    });
+}
+
+pub fn update_replay_overlay(
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
+    playback: Res<ReplayPlayback>,
+    mut text_query: Query<&mut Text, With<ReplayText>>,
+) {
+    let fps = config.fps as i32;
+    let status = if playback.paused {
+        String::from("paused")
+    } else {
+        format!("x{}", playback.speed)
+    };
+    let overlay = format!(
+        "Replay {} / {} {}\nSpace pause, Left/Right seek, Up/Down speed, Home restart",
+        format_race_time(current_frame.0, fps),
+        format_race_time(playback.inputs.frame_count(), fps),
+        status
+    );
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = overlay.clone();
+    }
+}
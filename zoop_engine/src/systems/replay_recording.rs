@@ -0,0 +1,50 @@
+use crate::domain::desync::Desynced;
+use crate::domain::frame_inputs::FrameInputs;
+use crate::domain::frames::*;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_state::GameState;
+use crate::domain::replay::{InputRecorder, Replay};
+use crate::services::replay::publish_replay;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+/// Saves the confirmed inputs once a round is over, or when the match ends early by a desync or closing the game.
+/// Every round saves the whole match again, so that the replay covers all rounds raced so far.
+pub fn save_replay(
+    config: Res<GameConfig>,
+    state: Res<GameState>,
+    confirmed_frame: Res<ConfirmedFrame>,
+    desynced: Res<Desynced>,
+    recorder: Option<Res<InputRecorder>>,
+    mut exit: EventReader<AppExit>,
+    mut saved_round: Local<Option<u16>>,
+) {
+    let is_exiting = exit.iter().count() > 0;
+    let Some(recorder) = recorder else {
+        return;
+    };
+    let is_race_confirmed = state.race.is_over && state.race.over_frame <= confirmed_frame.0;
+    let is_desynced = desynced.desync.is_some();
+    let round = state.match_state.round;
+    if *saved_round == Some(round) || !(is_race_confirmed || is_desynced || is_exiting) {
+        return;
+    }
+
+    let inputs = recorder.confirmed(confirmed_frame.0);
+    info!("Saving replay of {} frames", inputs.frame_count());
+    publish_replay(
+        Replay::record(config.as_ref(), inputs),
+        config.replay_file.as_deref(),
+    );
+    *saved_round = Some(round);
+}
+
+pub fn record_inputs(
+    current_frame: Res<CurrentFrame>,
+    inputs: Res<FrameInputs>,
+    recorder: Option<ResMut<InputRecorder>>,
+) {
+    if let Some(mut recorder) = recorder {
+        recorder.record(current_frame.0, inputs.as_ref());
+    }
+}
@@ -0,0 +1,141 @@
+use crate::domain::car_body::CarMeta;
+use crate::domain::frame_inputs::FrameInputs;
+use crate::domain::frames::*;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_state::{EntityPhysics, GameState};
+use crate::domain::physics_snapshot::PhysicsSnapshot;
+use crate::domain::player::Player;
+use crate::domain::rapier_rollback_state::RoundStartPhysics;
+use crate::domain::tire::{TireMeta, TirePhysics};
+use crate::systems::manage_scene::init_scene;
+use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Starts the next round once every player asked for a rematch after the race.
+/// Bodies are moved back to the starting grid instead of respawned, so that a rollback
+/// across the restart only has to restore rolled back components and the Rapier context.
+/// Rapier goes back to its state on the first frame, so contacts, joint impulses and
+/// broad phase pairs of the last round are gone, as if the cars were new.
+pub fn restart_round(
+    config: Res<GameConfig>,
+    inputs: Res<FrameInputs>,
+    current_frame: Res<CurrentFrame>,
+    mut state: ResMut<GameState>,
+    mut enable_physics_after: ResMut<EnablePhysicsAfter>,
+    mut rapier: ResMut<RapierContext>,
+    round_start: Res<RoundStartPhysics>,
+    mut car_query: Query<
+        (
+            &Player,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Velocity,
+            &mut ExternalForce,
+            &mut ExternalImpulse,
+        ),
+        With<CarMeta>,
+    >,
+    mut tire_query: Query<
+        (
+            &Player,
+            &TireMeta,
+            &mut TirePhysics,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Velocity,
+            &mut ExternalForce,
+            &mut ExternalImpulse,
+        ),
+        Without<CarMeta>,
+    >,
+) {
+    if !state.race.is_over {
+        return;
+    }
+
+    // Votes are kept, so players don't have to hold the key until everyone pressed it
+    for handle in 0..config.players.len() {
+        let (controls, _) = inputs.0[handle];
+        if controls.rematch() && !state.race.rematch_votes.contains(&handle) {
+            info!("Player {} wants a rematch", handle);
+            state.race.rematch_votes.push(handle);
+        }
+    }
+    if state.race.rematch_votes.len() < config.players.len() {
+        return;
+    }
+
+    let frame = current_frame.0;
+    let mut next = init_scene(config.as_ref());
+    next.match_state = state.match_state.next_round(config.best_of);
+    info!(
+        "Starting round {} on frame {}",
+        next.match_state.round, frame
+    );
+
+    let reset = |physics: &EntityPhysics,
+                 transform: &mut Transform,
+                 global_transform: &mut GlobalTransform,
+                 velocity: &mut Velocity,
+                 force: &mut ExternalForce,
+                 impulse: &mut ExternalImpulse| {
+        *transform = physics.transform;
+        *global_transform = GlobalTransform::from(physics.transform);
+        *velocity = physics.velocity;
+        *force = physics.force;
+        *impulse = physics.impulse;
+    };
+    for car in next.cars() {
+        for (player, mut transform, mut global_transform, mut velocity, mut force, mut impulse) in
+            car_query.iter_mut()
+        {
+            if player.handle == car.player.handle {
+                reset(
+                    &car.physics,
+                    &mut transform,
+                    &mut global_transform,
+                    &mut velocity,
+                    &mut force,
+                    &mut impulse,
+                );
+            }
+        }
+        for (
+            player,
+            meta,
+            mut tire_physics,
+            mut transform,
+            mut global_transform,
+            mut velocity,
+            mut force,
+            mut impulse,
+        ) in tire_query.iter_mut()
+        {
+            if player.handle == car.player.handle {
+                let tire = car.tire(meta.is_front, meta.is_right);
+                *tire_physics = tire.tire_physics;
+                reset(
+                    &tire.entity_physics,
+                    &mut transform,
+                    &mut global_transform,
+                    &mut velocity,
+                    &mut force,
+                    &mut impulse,
+                );
+            }
+        }
+    }
+
+    // Rapier's bodies of the first frame stand on the starting grid like the teleported ones
+    if !PhysicsSnapshot::restore(rapier.as_mut(), &round_start.0) {
+        error!("Failed to restore the physics state of the round start");
+    }
+
+    *state = next;
+    *enable_physics_after = EnablePhysicsAfter::with_default_offset(
+        frame,
+        config.fps as i32,
+        config.countdown_seconds as i32,
+    );
+}
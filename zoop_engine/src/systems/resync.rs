@@ -0,0 +1,394 @@
+use crate::domain::desync::*;
+use crate::domain::frames::*;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_state::GameState;
+use crate::domain::ggrs_config::GGRSConfig;
+use crate::domain::physics_snapshot::PhysicsSnapshot;
+use crate::domain::rapier_rollback_state::{PhysicsStateBuffers, RapierRollbackState};
+use crate::domain::resync::*;
+use crate::domain::tire::TirePhysics;
+use crate::services::websocket::NonBlockingWebSocket;
+use crate::systems::build_network::start_network_session;
+use crate::systems::rollback_rapier_context::EnablePhysicsAfter;
+use bevy::prelude::*;
+use bevy_ggrs::{Rollback, Session};
+use bevy_rapier2d::prelude::*;
+use ggrs::{Frame, NonBlockingSocket};
+use std::collections::{HashMap, HashSet};
+use zoop_shared::player_id::PlayerId;
+
+/// Keeps what was rolled back for recent frames, the authority sends a confirmed one
+pub fn record_resync_snapshot(
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
+    rapier_state: Res<RapierRollbackState>,
+    buffers: Res<PhysicsStateBuffers>,
+    game_state: Res<GameState>,
+    enable_physics_after: Res<EnablePhysicsAfter>,
+    body_query: Query<
+        (Entity, &Transform, Option<&Velocity>, Option<&TirePhysics>),
+        With<Rollback>,
+    >,
+    history: Option<ResMut<ResyncHistory>>,
+) {
+    let (Some(mut history), Some(rapier_bytes)) = (history, buffers.get(rapier_state.frame)) else {
+        return;
+    };
+
+    let mut bodies: Vec<ResyncBody> = body_query
+        .iter()
+        .map(|(entity, transform, velocity, tire)| ResyncBody {
+            entity: entity.to_bits(),
+            transform: *transform,
+            velocity: velocity.copied(),
+            tire: tire.copied(),
+        })
+        .collect();
+    bodies.sort_by_key(|body| body.entity);
+
+    history.push(
+        ResyncSnapshot {
+            frame: current_frame.0,
+            rapier_checksum: rapier_state.rapier_checksum,
+            rapier_state: rapier_bytes.to_vec(),
+            game_state: game_state.clone(),
+            enable_physics_after: *enable_physics_after,
+            bodies,
+        },
+        config.desync_max_frames as usize,
+    );
+}
+
+/// Recovers from a desync by restarting the session from the authority's state.
+/// Spectators are not part of it, they lose the race when the host's session restarts.
+pub fn process_resync(world: &mut World) {
+    let Some(RoomSocket(socket)) = world.get_resource::<RoomSocket>().cloned() else {
+        return;
+    };
+    let delta = world.resource::<Time>().delta_seconds();
+
+    world.resource_scope(|world, config: Mut<GameConfig>| {
+        world.resource_scope(|world, mut resync: Mut<Resync>| {
+            step_resync(world, &config, &socket, &mut resync, delta);
+        });
+    });
+}
+
+fn step_resync(
+    world: &mut World,
+    config: &GameConfig,
+    socket: &NonBlockingWebSocket,
+    resync: &mut Resync,
+    delta: f32,
+) {
+    let Some(local_handle) = config.players.iter().position(|p| p.is_local) else {
+        return;
+    };
+
+    // Nobody else drains the connection while there's no session
+    if !world.contains_resource::<Session<GGRSConfig>>() {
+        let _ = socket.clone().receive_all_messages();
+    }
+    let messages: Vec<(usize, ResyncMessage)> = socket
+        .take_resync_messages()
+        .into_iter()
+        .filter_map(|(from, message)| {
+            let handle = config.players.iter().position(|p| p.id == from)?;
+            let message = serde_json::from_str(&message).ok()?;
+            Some((handle, message))
+        })
+        .collect();
+
+    // A desync we noticed ourselves
+    let local_desync = world.resource::<Desynced>().desync.clone();
+    if let Some(desync) = local_desync {
+        if !resync.is_active() && !resync.gave_up {
+            broadcast(
+                config,
+                socket,
+                &ResyncMessage::Request {
+                    frame: desync.frame,
+                },
+            );
+            begin_resync(world, config, socket, resync, local_handle);
+        }
+    }
+
+    for (handle, message) in messages {
+        match message {
+            // Snapshots may arrive before the request has been processed
+            ResyncMessage::Request { frame } | ResyncMessage::SnapshotChunk { frame, .. }
+                if !resync.is_active() && !resync.gave_up =>
+            {
+                info!(
+                    "Player {} asked for a resynchronization of frame {}",
+                    handle, frame
+                );
+                world.remove_resource::<Session<GGRSConfig>>();
+                let mut desynced = world.resource_mut::<Desynced>();
+                if desynced.desync.is_none() {
+                    desynced.desync = Some(DesyncEvent {
+                        source: DesyncSource::Peer,
+                        frame,
+                        peer: Some(handle),
+                        local_checksum: 0,
+                        remote_checksum: 0,
+                    });
+                }
+                begin_resync(world, config, socket, resync, local_handle);
+                if let ResyncMessage::SnapshotChunk { .. } = message {
+                    receive_chunk(world, config, socket, resync, message);
+                }
+            }
+            ResyncMessage::SnapshotChunk { .. } => {
+                receive_chunk(world, config, socket, resync, message);
+            }
+            ResyncMessage::Loaded { frame } => {
+                if let ResyncPhase::AwaitingLoaded {
+                    frame: snapshot_frame,
+                    loaded,
+                } = &mut resync.phase
+                {
+                    if frame == *snapshot_frame {
+                        loaded.insert(handle);
+                    }
+                }
+            }
+            ResyncMessage::Restart { frame } => {
+                if resync.phase == (ResyncPhase::AwaitingRestart { frame }) {
+                    restart_session(world, config, socket, resync, frame);
+                }
+            }
+            ResyncMessage::Request { .. } => {}
+        }
+    }
+
+    // The authority restarts once everybody else loaded its snapshot
+    if let ResyncPhase::AwaitingLoaded { frame, loaded } = &resync.phase {
+        if loaded.len() + 1 >= config.players.len() {
+            let frame = *frame;
+            broadcast(config, socket, &ResyncMessage::Restart { frame });
+            restart_session(world, config, socket, resync, frame);
+        }
+    }
+
+    // Somebody left or never answered
+    if resync.is_active() {
+        resync.elapsed += delta;
+        if resync.elapsed > config.resync_timeout_seconds as f32 {
+            error!("Resynchronization timed out");
+            give_up(resync);
+        }
+    }
+}
+
+fn begin_resync(
+    world: &mut World,
+    config: &GameConfig,
+    socket: &NonBlockingWebSocket,
+    resync: &mut Resync,
+    local_handle: usize,
+) {
+    if resync.completed >= config.max_resyncs {
+        error!("Out of resynchronizations");
+        give_up(resync);
+        return;
+    }
+    resync.elapsed = 0.0;
+
+    if local_handle != 0 {
+        info!("Waiting for a snapshot from player 0");
+        resync.phase = ResyncPhase::AwaitingSnapshot {
+            chunks: HashMap::new(),
+        };
+        return;
+    }
+
+    // We're the authority, everybody continues from our latest confirmed frame
+    let confirmed_frame = world.resource::<ConfirmedFrame>().0;
+    let snapshot = world
+        .resource::<ResyncHistory>()
+        .confirmed(confirmed_frame)
+        .cloned();
+    let Some(snapshot) = snapshot else {
+        error!("No snapshot to resynchronize from");
+        give_up(resync);
+        return;
+    };
+    let Ok(json) = serde_json::to_string(&snapshot) else {
+        error!("Failed to serialize the resynchronization snapshot");
+        give_up(resync);
+        return;
+    };
+    // Peers shouldn't wait for an authority which can't load its own snapshot
+    if let Err(error) = load_snapshot(world, &snapshot) {
+        error!("{}", error);
+        give_up(resync);
+        return;
+    }
+    info!(
+        "Sending snapshot of frame {} with checksum {}",
+        snapshot.frame, snapshot.rapier_checksum
+    );
+
+    // JSON is ASCII only, so splitting bytes keeps every part valid
+    let chunks: Vec<&[u8]> = json.as_bytes().chunks(RESYNC_CHUNK_SIZE).collect();
+    for (index, chunk) in chunks.iter().enumerate() {
+        broadcast(
+            config,
+            socket,
+            &ResyncMessage::SnapshotChunk {
+                frame: snapshot.frame,
+                index,
+                count: chunks.len(),
+                data: String::from_utf8_lossy(chunk).to_string(),
+            },
+        );
+    }
+
+    resync.phase = ResyncPhase::AwaitingLoaded {
+        frame: snapshot.frame,
+        loaded: HashSet::new(),
+    };
+}
+
+fn receive_chunk(
+    world: &mut World,
+    config: &GameConfig,
+    socket: &NonBlockingWebSocket,
+    resync: &mut Resync,
+    message: ResyncMessage,
+) {
+    let (
+        ResyncPhase::AwaitingSnapshot { chunks },
+        ResyncMessage::SnapshotChunk {
+            frame,
+            index,
+            count,
+            data,
+        },
+    ) = (&mut resync.phase, message)
+    else {
+        return;
+    };
+    chunks.insert(index, data);
+    if chunks.len() < count {
+        return;
+    }
+
+    let json: String = (0..count)
+        .filter_map(|index| chunks.get(&index).map(|chunk| chunk.as_str()))
+        .collect();
+    let snapshot = match serde_json::from_str::<ResyncSnapshot>(&json) {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            error!("Failed to parse the resynchronization snapshot: {}", error);
+            give_up(resync);
+            return;
+        }
+    };
+    info!(
+        "Loading snapshot of frame {} with checksum {}",
+        snapshot.frame, snapshot.rapier_checksum
+    );
+    if let Err(error) = load_snapshot(world, &snapshot) {
+        error!("{}", error);
+        give_up(resync);
+        return;
+    }
+
+    if let Some(authority) = config.players.first() {
+        send(socket, &authority.id, &ResyncMessage::Loaded { frame });
+    }
+    resync.phase = ResyncPhase::AwaitingRestart { frame };
+}
+
+/// Loads rolled back state the same way a rollback does,
+/// nothing else is loaded when the physics state can't be restored
+fn load_snapshot(world: &mut World, snapshot: &ResyncSnapshot) -> Result<(), String> {
+    if !PhysicsSnapshot::restore(
+        world.resource_mut::<RapierContext>().as_mut(),
+        &snapshot.rapier_state,
+    ) {
+        return Err(format!(
+            "Failed to restore the physics state of frame {}",
+            snapshot.frame
+        ));
+    }
+    world
+        .resource_mut::<PhysicsStateBuffers>()
+        .insert(snapshot.frame, &snapshot.rapier_state);
+    world.insert_resource(RapierRollbackState {
+        frame: snapshot.frame,
+        rapier_checksum: snapshot.rapier_checksum,
+    });
+    world.insert_resource(snapshot.game_state.clone());
+    world.insert_resource(snapshot.enable_physics_after);
+    world.insert_resource(CurrentFrame(snapshot.frame));
+
+    for body in snapshot.bodies.iter() {
+        let Some(mut entity) = world.get_entity_mut(Entity::from_bits(body.entity)) else {
+            continue;
+        };
+        entity.insert((body.transform, GlobalTransform::from(body.transform)));
+        if let Some(velocity) = body.velocity {
+            entity.insert(velocity);
+        }
+        if let Some(tire) = body.tire {
+            entity.insert(tire);
+        }
+    }
+    Ok(())
+}
+
+fn restart_session(
+    world: &mut World,
+    config: &GameConfig,
+    socket: &NonBlockingWebSocket,
+    resync: &mut Resync,
+    frame: Frame,
+) {
+    info!("Restarting the session from frame {}", frame);
+
+    // Frame bookkeeping continues from the snapshot, as if nothing happened
+    world.insert_resource(SessionStartFrame(frame));
+    world.insert_resource(CurrentSessionFrame(frame));
+    world.insert_resource(ConfirmedFrame(frame));
+    world.insert_resource(ValidatableFrame::default());
+    world.insert_resource(RollbackStatus {
+        last_frame: frame,
+        ..default()
+    });
+
+    // Hashes of the old session can't be compared anymore
+    world.insert_resource(FrameHashes::default());
+    world.insert_resource(RxFrameHashes::default());
+    world.insert_resource(ResyncHistory::default());
+    world.insert_resource(Desynced::default());
+
+    world.insert_resource(Session::P2PSession(start_network_session(
+        config,
+        socket.clone(),
+    )));
+
+    resync.phase = ResyncPhase::Idle;
+    resync.elapsed = 0.0;
+    resync.completed += 1;
+}
+
+fn give_up(resync: &mut Resync) {
+    resync.phase = ResyncPhase::Idle;
+    resync.gave_up = true;
+}
+
+fn send(socket: &NonBlockingWebSocket, to: &PlayerId, message: &ResyncMessage) {
+    if let Ok(json) = serde_json::to_string(message) {
+        socket.send_resync(json, to);
+    }
+}
+
+fn broadcast(config: &GameConfig, socket: &NonBlockingWebSocket, message: &ResyncMessage) {
+    for player in config.players.iter().filter(|player| !player.is_local) {
+        send(socket, &player.id, message);
+    }
+}
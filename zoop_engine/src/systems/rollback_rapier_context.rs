@@ -1,16 +1,18 @@
 use crate::domain::checksum::*;
 use crate::domain::frames::*;
-use crate::domain::rapier_rollback_state::RapierRollbackState;
+use crate::domain::physics_snapshot::PhysicsSnapshot;
+use crate::domain::rapier_rollback_state::{PhysicsStateBuffers, RapierRollbackState};
 use bevy::prelude::*;
 use bevy_rapier2d::plugin::RapierContext;
 use bevy_rapier2d::prelude::*;
 use ggrs::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Resource, Hash, Reflect)]
 #[reflect(Hash)]
 pub struct PhysicsEnabled(pub bool);
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Resource, Hash, Reflect)]
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug, Resource, Hash, Reflect)]
 #[reflect(Hash, Resource, PartialEq)]
 pub struct EnablePhysicsAfter {
     pub start: Frame,
@@ -33,6 +35,16 @@ impl EnablePhysicsAfter {
         Self::new(offset, offset + (fps * load_seconds) as i32)
     }
 
+    /// Whole seconds left until the start, while the last `countdown_seconds` of the window run
+    pub fn countdown(&self, frame: Frame, fps: i32, countdown_seconds: i32) -> Option<i32> {
+        let frames_left = self.end - frame;
+        if self.start < frame && 0 < frames_left && frames_left <= fps * countdown_seconds {
+            Some((frames_left + fps - 1) / fps)
+        } else {
+            None
+        }
+    }
+
     pub fn is_enabled(&self, frame: Frame) -> bool {
         // Since the starting frame is calculated at the end,
         // when we rollback to the start frame we will have the enable after
@@ -67,6 +79,7 @@ pub fn toggle_physics(
 pub fn reset_rapier(
     mut commands: Commands,
     mut rapier: ResMut<RapierContext>,
+    mut buffers: ResMut<PhysicsStateBuffers>,
     collider_handles: Query<Entity, With<RapierColliderHandle>>,
     rb_handles: Query<Entity, With<RapierRigidBodyHandle>>,
 ) {
@@ -106,34 +119,27 @@ pub fn reset_rapier(
 
     // Serialize our "blank" slate for frame 0.
     // This is actually important because it is possible to rollback to this!
-    if let Ok(context_bytes) = bincode::serialize(rapier.as_ref()) {
-        let rapier_checksum = fletcher16(&context_bytes);
-        info!("Context hash at init: {}", rapier_checksum);
-
-        commands.insert_resource(RapierRollbackState {
-            rapier_state: Some(context_bytes),
-            rapier_checksum,
-        })
-    } else {
-        commands.insert_resource(RapierRollbackState::default());
-    }
+    let context_bytes = buffers.buffer_mut(0);
+    PhysicsSnapshot::save_into(rapier.as_ref(), context_bytes);
+    let rapier_checksum = fnv1a64(context_bytes);
+    info!("Context hash at init: {}", rapier_checksum);
+
+    commands.insert_resource(RapierRollbackState {
+        frame: 0,
+        rapier_checksum,
+    });
 }
 
 pub fn rollback_rapier_context(
     rollback_status: Res<RollbackStatus>,
     game_state: Res<RapierRollbackState>,
+    buffers: Res<PhysicsStateBuffers>,
     mut rapier: ResMut<RapierContext>,
 ) {
-    let mut checksum = game_state.rapier_checksum;
-    debug!("Context pre-hash at start: {:?}", checksum);
-
-    // Serialize our physics state for hashing, to display the state in-flight.
-    // This should not be necessary for this demo to work, as we will do the
-    // real checksum during `save_game_state` at the end of the pipeline.
-    if let Ok(context_bytes) = bincode::serialize(rapier.as_ref()) {
-        checksum = fletcher16(&context_bytes);
-        debug!("Context hash at start: {}", checksum);
-    }
+    debug!(
+        "Context pre-hash at start: {:?}",
+        game_state.rapier_checksum
+    );
 
     // Only restore our state if we are in a rollback.  This step is *critical*.
     // Only doing this during rollbacks saves us a step every frame.  Here, we
@@ -144,37 +150,22 @@ pub fn rollback_rapier_context(
     // You can also test that desync detection is working by disabling:
     // if false {
     if rollback_status.is_rollback && rollback_status.rollback_frame > 1 {
-        if let Some(state_context) = game_state.rapier_state.as_ref() {
-            if let Ok(context) = bincode::deserialize::<RapierContext>(state_context) {
-                // commands.insert_resource(context);
-                // *rapier = context;
-
-                // Inserting or replacing directly seems to screw up some of the
-                // crate-only properties.  So, we'll copy over each public
-                // property instead.
-                rapier.bodies = context.bodies;
-                rapier.broad_phase = context.broad_phase;
-                rapier.ccd_solver = context.ccd_solver;
-                rapier.colliders = context.colliders;
-                rapier.impulse_joints = context.impulse_joints;
-                rapier.integration_parameters = context.integration_parameters;
-                rapier.islands = context.islands;
-                rapier.multibody_joints = context.multibody_joints;
-                rapier.narrow_phase = context.narrow_phase;
-                rapier.query_pipeline = context.query_pipeline;
-
-                // pipeline is not serialized
-                // rapier.pipeline = context.pipeline;
-            }
-        }
-
-        // Again, not necessary for the demo, just to show the rollback changes
-        // as they occur.
-        if let Ok(context_bytes) = bincode::serialize(rapier.as_ref()) {
-            debug!(
+        let restored = buffers
+            .get(game_state.frame)
+            .map(|state_context| PhysicsSnapshot::restore(rapier.as_mut(), state_context));
+        match restored {
+            Some(true) => debug!(
                 "Context hash after rollback: {}",
-                fletcher16(&context_bytes)
-            );
+                game_state.rapier_checksum
+            ),
+            Some(false) => error!(
+                "Failed to restore the physics state of frame {}",
+                game_state.frame
+            ),
+            None => error!(
+                "Physics state of frame {} is no longer kept",
+                game_state.frame
+            ),
         }
     }
 }
@@ -1,29 +1,40 @@
-use crate::domain::checksum::fletcher16;
-use crate::domain::desync::FrameHashes;
+use crate::domain::checksum::{fnv1a64, StateChecksums};
+use crate::domain::desync::*;
 use crate::domain::frames::*;
 use crate::domain::game_config::GameConfig;
-use crate::domain::rapier_rollback_state::RapierRollbackState;
+use crate::domain::game_state::GameState;
+use crate::domain::physics_snapshot::PhysicsSnapshot;
+use crate::domain::rapier_rollback_state::*;
 use bevy::prelude::*;
 use bevy_rapier2d::plugin::RapierContext;
 
 pub fn save_rapier_context(
     config: Res<GameConfig>,
     mut game_state: ResMut<RapierRollbackState>,
+    mut buffers: ResMut<PhysicsStateBuffers>,
+    mut round_start: ResMut<RoundStartPhysics>,
     rapier: Res<RapierContext>,
+    state: Res<GameState>,
     mut hashes: ResMut<FrameHashes>,
     confirmed_frame: Res<ConfirmedFrame>,
     current_frame: Res<CurrentFrame>,
+    mut desyncs: EventWriter<DesyncEvent>,
 ) {
     // This serializes our context every frame.  It's not great, but works to
     // integrate the two plugins.  To do less of it, we would need to change
     // bevy_ggrs to serialize arbitrary structs like this one in addition to
     // component tracking.  If you need this to happen less, I'd recommend not
     // using the plugin and implementing GGRS yourself.
-    if let Ok(context_bytes) = bincode::serialize(rapier.as_ref()) {
+    // Only the parts a step depends on are saved, see `PhysicsSnapshot`.
+    let context_bytes = buffers.buffer_mut(current_frame.0);
+    if let Some(layout) = PhysicsSnapshot::save_into(rapier.as_ref(), context_bytes) {
         debug!("Context hash before save: {}", game_state.rapier_checksum);
-        game_state.rapier_checksum = fletcher16(&context_bytes);
-        game_state.rapier_state = Some(context_bytes);
+        game_state.frame = current_frame.0;
+        game_state.rapier_checksum = fnv1a64(context_bytes);
         debug!("Context hash after save: {}", game_state.rapier_checksum);
+        if round_start.0.is_empty() {
+            round_start.0 = context_bytes.clone();
+        }
 
         if let Some(frame_hash) = hashes
             .0
@@ -32,10 +43,16 @@ pub fn save_rapier_context(
             if frame_hash.frame == current_frame.0 && frame_hash.sent {
                 // If this frame hash has already been sent and its the
                 // same one then the hashes better damn well match
-                assert_eq!(
-                    frame_hash.rapier_checksum, game_state.rapier_checksum,
-                    "INTEGRITY BREACHED"
-                );
+                if frame_hash.rapier_checksum != game_state.rapier_checksum {
+                    error!("INTEGRITY BREACHED on frame {}", frame_hash.frame);
+                    desyncs.send(DesyncEvent {
+                        source: DesyncSource::Resimulation,
+                        frame: frame_hash.frame,
+                        peer: None,
+                        local_checksum: game_state.rapier_checksum,
+                        remote_checksum: frame_hash.rapier_checksum,
+                    });
+                }
                 info!(
                     "Integrity challenged of frame {}: {} vs {}",
                     frame_hash.frame, frame_hash.rapier_checksum, game_state.rapier_checksum
@@ -44,6 +61,7 @@ pub fn save_rapier_context(
 
             frame_hash.frame = current_frame.0;
             frame_hash.rapier_checksum = game_state.rapier_checksum;
+            frame_hash.state_checksums = StateChecksums::of(context_bytes, &layout, state.as_ref());
             frame_hash.sent = false;
             frame_hash.validated = false;
             debug!("confirmed frame: {:?}", confirmed_frame);
@@ -0,0 +1,59 @@
+use crate::domain::frames::*;
+use crate::domain::game_config::GameConfig;
+use crate::domain::game_mode::GameMode;
+use crate::domain::rapier_rollback_state::RapierRollbackState;
+use crate::domain::sync_test::{SyncTestChecksums, SyncTestResult};
+use crate::services::sync_test::*;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+/// Compares the Rapier checksum of every resimulated frame with its first simulation
+pub fn check_sync_test(
+    config: Res<GameConfig>,
+    current_frame: Res<CurrentFrame>,
+    rapier_state: Res<RapierRollbackState>,
+    mut checksums: ResMut<SyncTestChecksums>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let GameMode::SyncTest(sync_test) = &config.mode else {
+        return;
+    };
+    if sync_test_result().is_some() {
+        return;
+    }
+
+    let frame = current_frame.0;
+    let resimulated_checksum = rapier_state.rapier_checksum;
+    match checksums.0.get(&frame) {
+        Some(checksum) if *checksum != resimulated_checksum => {
+            let result = SyncTestResult::Mismatch {
+                frame,
+                checksum: *checksum,
+                resimulated_checksum,
+            };
+            error!("{}", result);
+            publish_sync_test_result(result);
+            exit.send(AppExit);
+            return;
+        }
+        Some(_) => {}
+        None => {
+            checksums.0.insert(frame, resimulated_checksum);
+        }
+    }
+
+    // Frames older than the check distance won't be resimulated anymore
+    let oldest_frame = frame - sync_test.check_distance as i32 - 1;
+    checksums
+        .0
+        .retain(|checked_frame, _| *checked_frame >= oldest_frame);
+
+    if let Some(frames) = sync_test.frames {
+        if frame >= frames {
+            let result = SyncTestResult::Passed { frames };
+            info!("{}", result);
+            publish_sync_test_result(result);
+            exit.send(AppExit);
+        }
+    }
+}
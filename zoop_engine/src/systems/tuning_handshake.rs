@@ -0,0 +1,106 @@
+use crate::domain::game_config::GameConfig;
+use crate::domain::ggrs_config::GGRSConfig;
+use crate::domain::handshake::*;
+use crate::domain::resync::RoomSocket;
+use crate::services::tuning_handshake::publish_tuning_mismatch;
+use crate::services::websocket::NonBlockingWebSocket;
+use crate::systems::build_network::start_network_session;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_ggrs::Session;
+use ggrs::NonBlockingSocket;
+use zoop_shared::player_id::PlayerId;
+
+/// Starts the session once every player confirmed simulating with the same handling.
+/// Keeps answering handshakes afterwards, for players which missed ours.
+pub fn process_tuning_handshake(world: &mut World) {
+    let Some(RoomSocket(socket)) = world.get_resource::<RoomSocket>().cloned() else {
+        return;
+    };
+    if !world.contains_resource::<TuningHandshake>() {
+        return;
+    }
+    let delta = world.resource::<Time>().delta_seconds();
+
+    world.resource_scope(|world, config: Mut<GameConfig>| {
+        world.resource_scope(|world, mut handshake: Mut<TuningHandshake>| {
+            step_handshake(world, &config, &socket, &mut handshake, delta);
+        });
+    });
+}
+
+fn step_handshake(
+    world: &mut World,
+    config: &GameConfig,
+    socket: &NonBlockingWebSocket,
+    handshake: &mut TuningHandshake,
+    delta: f32,
+) {
+    if handshake.mismatch.is_some() {
+        return;
+    }
+
+    // Nobody else drains the connection while there's no session
+    if !world.contains_resource::<Session<GGRSConfig>>() {
+        let _ = socket.clone().receive_all_messages();
+    }
+    for (from, message) in socket.take_handshake_messages() {
+        let Some(handle) = config.players.iter().position(|p| p.id == from) else {
+            continue;
+        };
+        let Ok(message) = serde_json::from_str::<HandshakeMessage>(&message) else {
+            continue;
+        };
+        if !message.is_reply {
+            send(socket, &from, &handshake.fingerprint, true);
+        }
+        handshake
+            .received
+            .insert(handle, message.tuning_fingerprint);
+    }
+
+    if let Some(mismatch) = handshake.find_mismatch() {
+        error!("{}", mismatch);
+        publish_tuning_mismatch(mismatch.to_string());
+        handshake.mismatch = Some(mismatch);
+        // Browsers keep showing the reason on the HUD instead
+        if !cfg!(target_arch = "wasm32") {
+            world.send_event(AppExit);
+        }
+        return;
+    }
+    if handshake.started {
+        return;
+    }
+
+    if handshake.received.len() + 1 >= config.players.len() {
+        info!(
+            "Every player drives with car tuning {}",
+            handshake.fingerprint
+        );
+        world.insert_resource(Session::P2PSession(start_network_session(
+            config,
+            socket.clone(),
+        )));
+        handshake.started = true;
+        return;
+    }
+
+    handshake.since_sent += delta;
+    if handshake.since_sent >= HANDSHAKE_RESEND_SECONDS {
+        handshake.since_sent = 0.0;
+        for player in config.players.iter().filter(|player| !player.is_local) {
+            send(socket, &player.id, &handshake.fingerprint, false);
+        }
+    }
+}
+
+fn send(socket: &NonBlockingWebSocket, to: &PlayerId, fingerprint: &str, is_reply: bool) {
+    let message = HandshakeMessage {
+        tuning_fingerprint: fingerprint.to_string(),
+        is_reply,
+    };
+    if let Ok(json) = serde_json::to_string(&message) {
+        socket.send_handshake(json, to);
+    }
+}
@@ -3,7 +3,7 @@ use actix::*;
 
 use std::collections::HashMap;
 use zoop_shared::player_id::PlayerId;
-use zoop_shared::player_message::PlayerMessage;
+use zoop_shared::player_message::{PlayerMessage, PlayerMessageKind};
 use zoop_shared::room_id::RoomId;
 
 /// Game room comms
@@ -92,9 +92,14 @@ impl Handler<FromToPlayer> for GameRoom {
                 should_drop = false;
             }
 
-            if !should_drop {
+            // Resynchronization and handshakes have no retries, unlike GGRS
+            let is_reliable = from_to.message.kind != PlayerMessageKind::Ggrs;
+            if is_reliable || !should_drop {
                 let _ = address.try_send(FromPlayer {
-                    message: PlayerMessage::from(from_to.from, from_to.message.message),
+                    message: PlayerMessage {
+                        address: from_to.from,
+                        ..from_to.message
+                    },
                 });
             }
         }
@@ -11,15 +11,43 @@ use actix_web::HttpResponse;
 use actix_web::{get, post, Result};
 use actix_web_actors::ws;
 
+use serde::Deserialize;
 use std::sync::Mutex;
 
+use zoop_shared::car_class::CarClass;
+use zoop_shared::car_tuning::{CarTuning, CarTuningError};
 use zoop_shared::player_id::PlayerId;
 use zoop_shared::room_config::GameRoomConfig;
 use zoop_shared::room_id::RoomId;
 
+/// Optional settings of a new room
+#[derive(Deserialize)]
+pub struct GameRoomOptions {
+    pub best_of: Option<u16>,
+    pub car_class: Option<CarClass>,
+}
+
+/// Optional settings of a joining player
+#[derive(Deserialize)]
+pub struct GamePlayerOptions {
+    pub car_class: Option<CarClass>,
+}
+
+/// Rooms without a tuning body drive with the default handling,
+/// anything else has to be a valid tuning regardless of its content type
+fn parse_tuning(body: &[u8]) -> Result<CarTuning, CarTuningError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(CarTuning::default());
+    }
+    let json = std::str::from_utf8(body).map_err(|e| CarTuningError::Parse(e.to_string()))?;
+    CarTuning::from_json(json)
+}
+
 #[post("/game/new/by/{player_id}/ticket/{ticket}/player_count/{player_count}")]
 pub async fn game_room_spawn(
     path: web::Path<(PlayerId, Ticket, u32)>,
+    options: web::Query<GameRoomOptions>,
+    tuning_body: web::Bytes,
     users_mutex: Data<Mutex<Users>>,
     lobby_mutex: Data<Mutex<GameLobby>>,
 ) -> Result<web::Json<RoomId>, AppError> {
@@ -27,8 +55,15 @@ pub async fn game_room_spawn(
     let users = users_mutex.lock().unwrap();
     let is_user_with_ticket = users.has(player_id, ticket.clone());
 
+    let best_of = options.best_of.unwrap_or(1).max(1);
+    let tuning = parse_tuning(&tuning_body);
+
     if player_count.clone() <= 1 {
         Err(AppError::NotEnoughPlayers())
+    } else if let Err(error) = tuning {
+        Err(AppError::InvalidCarTuning {
+            message: error.to_string(),
+        })
     } else if !is_user_with_ticket {
         Err(AppError::UserTicketWrong())
     } else {
@@ -36,7 +71,14 @@ pub async fn game_room_spawn(
         let address = RoomId::new();
         println!("Attempting to create room {}", &address);
         lobby
-            .create(address.clone(), player_count.clone(), player_id.clone())
+            .create(
+                address.clone(),
+                player_count.clone(),
+                best_of,
+                tuning.unwrap_or_default(),
+                player_id.clone(),
+                options.car_class.unwrap_or_default(),
+            )
             .map(|_| web::Json(address))
     }
 }
@@ -44,6 +86,7 @@ pub async fn game_room_spawn(
 #[post("/game/join/{room_id}/by/{player_id}/ticket/{ticket}")]
 pub async fn game_room_join(
     path: web::Path<(RoomId, PlayerId, Ticket)>,
+    options: web::Query<GamePlayerOptions>,
     users_mutex: Data<Mutex<Users>>,
     lobby_mutex: Data<Mutex<GameLobby>>,
 ) -> Result<web::Json<()>, AppError> {
@@ -60,7 +103,33 @@ pub async fn game_room_join(
         };
         println!("Attempting to enqueue {} in room {}", &player_id, &room_id);
         lobby
-            .enqueue_player(room_id.clone(), player_id.clone(), starter)
+            .enqueue_player(
+                room_id.clone(),
+                player_id.clone(),
+                options.car_class.unwrap_or_default(),
+                starter,
+            )
+            .map(|_| web::Json(()))
+    }
+}
+
+#[post("/game/spectate/{room_id}/by/{player_id}/ticket/{ticket}")]
+pub async fn game_room_spectate(
+    path: web::Path<(RoomId, PlayerId, Ticket)>,
+    users_mutex: Data<Mutex<Users>>,
+    lobby_mutex: Data<Mutex<GameLobby>>,
+) -> Result<web::Json<()>, AppError> {
+    let (room_id, player_id, ticket) = path.as_ref();
+    let users = users_mutex.lock().unwrap();
+    let is_user_with_ticket = users.has(player_id, ticket.clone());
+
+    if !is_user_with_ticket {
+        Err(AppError::UserTicketWrong())
+    } else {
+        let mut lobby = lobby_mutex.lock().unwrap();
+        println!("Attempting to spectate room {} as {}", &room_id, &player_id);
+        lobby
+            .enqueue_spectator(room_id.clone(), player_id.clone())
             .map(|_| web::Json(()))
     }
 }
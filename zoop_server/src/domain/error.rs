@@ -54,8 +54,14 @@ pub enum AppError {
     RoomNotResponding(),
     #[display(fmt = "Room is already full of players")]
     RoomFull(),
+    #[display(fmt = "Game already started, spectators must join before the start")]
+    GameAlreadyStarted(),
+    #[display(fmt = "Players can't spectate their own game")]
+    AlreadyPlaying(),
     #[display(fmt = "Unrecognized or bad message received")]
     BadMessage(),
+    #[display(fmt = "{}", message)]
+    InvalidCarTuning { message: String },
 }
 
 impl actix_web::error::ResponseError for AppError {
@@ -73,7 +79,10 @@ impl actix_web::error::ResponseError for AppError {
             AppError::GameNotReady { .. } => StatusCode::NOT_FOUND,
             AppError::RoomNotResponding { .. } => StatusCode::BAD_GATEWAY,
             AppError::RoomFull { .. } => StatusCode::BAD_REQUEST,
+            AppError::GameAlreadyStarted { .. } => StatusCode::BAD_REQUEST,
+            AppError::AlreadyPlaying { .. } => StatusCode::BAD_REQUEST,
             AppError::BadMessage { .. } => StatusCode::BAD_REQUEST,
+            AppError::InvalidCarTuning { .. } => StatusCode::BAD_REQUEST,
         }
     }
     fn error_response(&self) -> HttpResponse {
@@ -8,6 +8,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use tokio::sync::Notify;
+use zoop_shared::car_class::CarClass;
+use zoop_shared::car_tuning::CarTuning;
 use zoop_shared::player_id::PlayerId;
 use zoop_shared::room_config::GameRoomConfig;
 use zoop_shared::room_id::RoomId;
@@ -16,8 +18,12 @@ use zoop_shared::room_id::RoomId;
 pub struct GameLobbyRoomMetadata {
     pub room_id: RoomId,
     pub player_count: u32,
+    pub best_of: u16,
+    pub tuning: CarTuning,
     pub created_by: PlayerId,
     pub players: Cell<Vec<PlayerId>>,
+    pub spectators: Cell<Vec<PlayerId>>,
+    pub car_classes: Cell<HashMap<PlayerId, CarClass>>,
     pub address: Cell<Option<Addr<GameRoom>>>,
     pub ready_notification: Arc<Notify>,
 }
@@ -29,6 +35,11 @@ impl GameLobbyRoomMetadata {
     fn config(&mut self) -> GameRoomConfig {
         GameRoomConfig {
             players: self.players.get_mut().clone(),
+            spectators: self.spectators.get_mut().clone(),
+            best_of: self.best_of,
+            tuning: self.tuning.clone(),
+            tuning_fingerprint: Some(self.tuning.fingerprint()),
+            car_classes: self.car_classes.get_mut().clone(),
         }
     }
 }
@@ -43,15 +54,22 @@ impl GameLobby {
         &mut self,
         room_id: RoomId,
         player_count: u32,
+        best_of: u16,
+        tuning: CarTuning,
         by: PlayerId,
+        car_class: CarClass,
     ) -> Result<(), AppError> {
         match self.games.insert(
             room_id.clone(),
             GameLobbyRoomMetadata {
                 room_id,
                 player_count,
+                best_of,
+                tuning,
                 created_by: by.clone(),
-                players: Cell::new(vec![by]),
+                players: Cell::new(vec![by.clone()]),
+                spectators: Cell::new(vec![]),
+                car_classes: Cell::new(HashMap::from([(by, car_class)])),
                 address: Cell::new(None),
                 ready_notification: Arc::new(Notify::new()),
             },
@@ -65,6 +83,7 @@ impl GameLobby {
         &mut self,
         room_id: RoomId,
         player: PlayerId,
+        car_class: CarClass,
         starter: fn(RoomId, u32) -> Addr<GameRoom>,
     ) -> Result<(), AppError> {
         match self.games.get_mut(&room_id) {
@@ -76,6 +95,7 @@ impl GameLobby {
                 let address = room.address.get_mut();
 
                 if (players.len() + 1) == (room.player_count as usize) {
+                    room.car_classes.get_mut().insert(player.clone(), car_class);
                     players.push(player);
                     println!("All players joined, starting room {}", &room.room_id);
                     *address = Some(starter(room.room_id.clone(), room.player_count));
@@ -83,6 +103,7 @@ impl GameLobby {
 
                     Ok(())
                 } else if players.len() < (room.player_count as usize) {
+                    room.car_classes.get_mut().insert(player.clone(), car_class);
                     players.push(player);
                     Ok(())
                 } else {
@@ -92,6 +113,33 @@ impl GameLobby {
         }
     }
 
+    // Spectators don't take up player slots, but the host has to know about them
+    // when starting its GGRS session, which can't add spectators later on
+    pub fn enqueue_spectator(
+        &mut self,
+        room_id: RoomId,
+        spectator: PlayerId,
+    ) -> Result<(), AppError> {
+        match self.games.get_mut(&room_id) {
+            None => Err(AppError::GameDoesNotExist {
+                id: room_id.clone(),
+            }),
+            Some(room) => {
+                if room.is_ready() {
+                    Err(AppError::GameAlreadyStarted())
+                } else if room.players.get_mut().contains(&spectator) {
+                    Err(AppError::AlreadyPlaying())
+                } else {
+                    let spectators = room.spectators.get_mut();
+                    if !spectators.contains(&spectator) {
+                        spectators.push(spectator);
+                    }
+                    Ok(())
+                }
+            }
+        }
+    }
+
     // Right(None) - game is ready
     // Right(Some(notify)) - you'll be notified when the game's ready
     // Left - game does not exist
@@ -117,9 +165,7 @@ impl GameLobby {
             }),
             Some(room) => {
                 if room.is_ready() {
-                    Ok(GameRoomConfig {
-                        players: room.players.get_mut().clone(),
-                    })
+                    Ok(room.config())
                 } else {
                     Err(AppError::GameNotReady())
                 }
@@ -33,6 +33,7 @@ async fn main() -> std::io::Result<()> {
             .app_data(users.clone())
             .service(game_room_spawn)
             .service(game_room_join)
+            .service(game_room_spectate)
             .service(game_room_ready)
             .service(game_room_connect)
             .service(game_room_config)
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Car picked by a player in the lobby, the engine's catalogue defines how it drives
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CarClass {
+    #[default]
+    Hatchback,
+    Truck,
+    Kart,
+}
+
+impl CarClass {
+    pub const ALL: [CarClass; 3] = [CarClass::Hatchback, CarClass::Truck, CarClass::Kart];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CarClass::Hatchback => "hatchback",
+            CarClass::Truck => "truck",
+            CarClass::Kart => "kart",
+        }
+    }
+}
+
+impl fmt::Display for CarClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for CarClass {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<CarClass, String> {
+        CarClass::ALL
+            .into_iter()
+            .find(|class| class.name() == name)
+            .ok_or_else(|| format!("Unknown car class '{}'", name))
+    }
+}
@@ -0,0 +1,259 @@
+use crate::checksum::fnv1a64;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Handling of every car in a match, in meters and degrees.
+/// Missing values fall back to the defaults, so a file only has to list what it changes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CarTuning {
+    pub acceleration_force: f32,
+    pub reversing_force: f32,
+    pub breaking_force: f32,
+    /// How strongly tires resist sliding sideways, from 0.0 to 1.0
+    pub friction_force: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// How fast tires turn while steering
+    pub steering_degrees_per_tick: f32,
+    pub max_steering_degrees: f32,
+    /// Slip angle at which tires have lost half of their grip, lower values drift sooner
+    pub half_grip_slip_degrees: f32,
+    /// Grip left on the rear tires while the handbrake is pulled, from 0.0 to 1.0
+    pub handbrake_grip: f32,
+    /// Share of the drive force going to the front axle, from 0.0 (rear wheel drive) to 1.0
+    /// (front wheel drive). Replaces the drivetrain of every car class when set.
+    pub drive_front_share: Option<f32>,
+    /// Rear wheel angle relative to the front wheel angle, from -1.0 to 1.0, positive values
+    /// turn against the front. Replaces the rear steering of every car class when set.
+    pub rear_steering_ratio: Option<f32>,
+}
+
+impl Default for CarTuning {
+    fn default() -> Self {
+        CarTuning {
+            acceleration_force: 140.0,
+            reversing_force: 100.0,
+            breaking_force: 300.0,
+            friction_force: 0.5,
+            linear_damping: 5.0,
+            angular_damping: 0.1,
+            steering_degrees_per_tick: 15.0,
+            max_steering_degrees: 35.0,
+            half_grip_slip_degrees: 8.0,
+            handbrake_grip: 0.2,
+            drive_front_share: None,
+            rear_steering_ratio: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CarTuningError {
+    Io(String),
+    Parse(String),
+    OutOfRange {
+        field: &'static str,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+}
+impl fmt::Display for CarTuningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CarTuningError::Io(e) => write!(f, "Failed to read car tuning file: {}", e),
+            CarTuningError::Parse(e) => write!(f, "Failed to parse car tuning: {}", e),
+            CarTuningError::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "Car tuning {} is {}, it must be between {} and {}",
+                field, value, min, max
+            ),
+        }
+    }
+}
+
+impl CarTuning {
+    pub fn from_json(json: &str) -> Result<CarTuning, CarTuningError> {
+        let tuning: CarTuning =
+            serde_json::from_str(json).map_err(|e| CarTuningError::Parse(e.to_string()))?;
+        tuning.validate()?;
+        Ok(tuning)
+    }
+
+    pub fn from_file(path: &Path) -> Result<CarTuning, CarTuningError> {
+        let json = std::fs::read_to_string(path).map_err(|e| CarTuningError::Io(e.to_string()))?;
+        CarTuning::from_json(&json)
+    }
+
+    fn fields(&self) -> [(&'static str, f32, f32, f32); 10] {
+        [
+            ("acceleration_force", self.acceleration_force, 0.0, 10000.0),
+            ("reversing_force", self.reversing_force, 0.0, 10000.0),
+            ("breaking_force", self.breaking_force, 0.0, 10000.0),
+            ("friction_force", self.friction_force, 0.0, 1.0),
+            ("linear_damping", self.linear_damping, 0.0, 100.0),
+            ("angular_damping", self.angular_damping, 0.0, 100.0),
+            (
+                "steering_degrees_per_tick",
+                self.steering_degrees_per_tick,
+                0.1,
+                90.0,
+            ),
+            ("max_steering_degrees", self.max_steering_degrees, 1.0, 89.0),
+            (
+                "half_grip_slip_degrees",
+                self.half_grip_slip_degrees,
+                0.5,
+                90.0,
+            ),
+            ("handbrake_grip", self.handbrake_grip, 0.0, 1.0),
+        ]
+    }
+
+    /// Fields which keep what the car class does when missing
+    fn overrides(&self) -> [(&'static str, Option<f32>, f32, f32); 2] {
+        [
+            ("drive_front_share", self.drive_front_share, 0.0, 1.0),
+            ("rear_steering_ratio", self.rear_steering_ratio, -1.0, 1.0),
+        ]
+    }
+
+    /// Rejects values which would make cars undrivable or the simulation unstable
+    pub fn validate(&self) -> Result<(), CarTuningError> {
+        let overrides = self
+            .overrides()
+            .into_iter()
+            .filter_map(|(field, value, min, max)| value.map(|value| (field, value, min, max)));
+        for (field, value, min, max) in self.fields().into_iter().chain(overrides) {
+            // NaN fails the range check as well
+            if !(min..=max).contains(&value) {
+                return Err(CarTuningError::OutOfRange {
+                    field,
+                    value,
+                    min,
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Hash of the exact values, equal on every peer which simulates with the same tuning
+    pub fn hash(&self) -> u64 {
+        let mut bytes: Vec<u8> = self
+            .fields()
+            .iter()
+            .flat_map(|(_, value, _, _)| value.to_le_bytes())
+            .collect();
+        for (_, value, _, _) in self.overrides() {
+            match value {
+                None => bytes.push(0),
+                Some(value) => {
+                    bytes.push(1);
+                    bytes.extend(value.to_le_bytes());
+                }
+            }
+        }
+        fnv1a64(&bytes)
+    }
+
+    /// The hash as hex, JavaScript can't represent every 64-bit number
+    pub fn fingerprint(&self) -> String {
+        format!("{:016x}", self.hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        let tuning = CarTuning {
+            friction_force: 1.5,
+            ..CarTuning::default()
+        };
+        assert_eq!(
+            tuning.validate(),
+            Err(CarTuningError::OutOfRange {
+                field: "friction_force",
+                value: 1.5,
+                min: 0.0,
+                max: 1.0,
+            })
+        );
+        assert!(CarTuning::from_json(r#"{"max_steering_degrees": 120.0}"#).is_err());
+        assert!(CarTuning::from_json(r#"{"rear_steering_ratio": -2.0}"#).is_err());
+    }
+
+    #[test]
+    fn rejects_nan() {
+        let tuning = CarTuning {
+            acceleration_force: f32::NAN,
+            ..CarTuning::default()
+        };
+        assert!(matches!(
+            tuning.validate(),
+            Err(CarTuningError::OutOfRange {
+                field: "acceleration_force",
+                ..
+            })
+        ));
+        let tuning = CarTuning {
+            drive_front_share: Some(f32::NAN),
+            ..CarTuning::default()
+        };
+        assert!(tuning.validate().is_err());
+    }
+
+    #[test]
+    fn missing_values_fall_back_to_defaults() {
+        let tuning = CarTuning::from_json(r#"{"acceleration_force": 200.0}"#).unwrap();
+        assert_eq!(
+            tuning,
+            CarTuning {
+                acceleration_force: 200.0,
+                ..CarTuning::default()
+            }
+        );
+        assert_eq!(CarTuning::from_json("{}").unwrap(), CarTuning::default());
+        assert!(matches!(
+            CarTuning::from_json("{"),
+            Err(CarTuningError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn fingerprint_survives_json_round_trip() {
+        let tuning = CarTuning {
+            // Not exactly representable, the round trip has to keep every bit
+            friction_force: 0.3,
+            half_grip_slip_degrees: 7.7,
+            drive_front_share: Some(0.4),
+            ..CarTuning::default()
+        };
+        let json = serde_json::to_string(&tuning).unwrap();
+        let parsed = CarTuning::from_json(&json).unwrap();
+        assert_eq!(parsed.fingerprint(), tuning.fingerprint());
+        assert_ne!(parsed.fingerprint(), CarTuning::default().fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_tells_missing_overrides_apart() {
+        let with_override = CarTuning {
+            rear_steering_ratio: Some(0.0),
+            ..CarTuning::default()
+        };
+        assert_ne!(
+            with_override.fingerprint(),
+            CarTuning::default().fingerprint()
+        );
+    }
+}
@@ -0,0 +1,14 @@
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Computes the 64-bit FNV-1a hash: <https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function>
+pub fn fnv1a64(data: &[u8]) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    hash
+}
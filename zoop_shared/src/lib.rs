@@ -1,5 +1,9 @@
+pub mod car_class;
+pub mod car_tuning;
+pub mod checksum;
 pub mod network_player;
 pub mod player_id;
 pub mod player_message;
+pub mod race_result;
 pub mod room_config;
 pub mod room_id;
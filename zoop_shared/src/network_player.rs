@@ -15,6 +15,13 @@ impl NetworkPlayer {
             is_local: false,
         }
     }
+    pub fn spectator(id: PlayerId, is_local: bool) -> NetworkPlayer {
+        NetworkPlayer {
+            id,
+            is_spectator: true,
+            is_local,
+        }
+    }
     pub fn local_player(id: PlayerId) -> NetworkPlayer {
         NetworkPlayer {
             id,
@@ -1,22 +1,52 @@
 use crate::player_id::PlayerId;
 use serde::{Deserialize, Serialize};
 
+/// Who a message is meant for on the receiving end
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlayerMessageKind {
+    /// GGRS session traffic, may be dropped
+    #[default]
+    Ggrs,
+    /// State resynchronization after a desync, must be delivered
+    Resync,
+    /// Agreement on the simulated handling before the session starts, must be delivered
+    Handshake,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PlayerMessage {
     pub address: PlayerId,
     pub message: String, // Bevy JSON message
+    #[serde(default)]
+    pub kind: PlayerMessageKind,
 }
 impl PlayerMessage {
     pub fn to(to: PlayerId, message: String) -> PlayerMessage {
         PlayerMessage {
             address: to,
             message,
+            kind: PlayerMessageKind::Ggrs,
         }
     }
     pub fn from(from: PlayerId, message: String) -> PlayerMessage {
         PlayerMessage {
             address: from,
             message,
+            kind: PlayerMessageKind::Ggrs,
+        }
+    }
+    pub fn resync_to(to: PlayerId, message: String) -> PlayerMessage {
+        PlayerMessage {
+            address: to,
+            message,
+            kind: PlayerMessageKind::Resync,
+        }
+    }
+    pub fn handshake_to(to: PlayerId, message: String) -> PlayerMessage {
+        PlayerMessage {
+            address: to,
+            message,
+            kind: PlayerMessageKind::Handshake,
         }
     }
 }
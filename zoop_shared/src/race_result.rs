@@ -0,0 +1,22 @@
+use crate::player_id::PlayerId;
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the stdout line a native game prints its race result on
+pub const RACE_RESULT_PREFIX: &str = "RACE_RESULT ";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RaceStanding {
+    pub player: PlayerId,
+    /// Final position, starting from 1
+    pub position: u32,
+    pub laps: u32,
+    pub finished: bool,
+    /// Time from the start signal until crossing the finish line
+    pub race_seconds: Option<f32>,
+}
+
+/// Final standings of a race, identical on every peer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RaceResult {
+    pub standings: Vec<RaceStanding>,
+}
@@ -1,8 +1,30 @@
+use crate::car_class::CarClass;
+use crate::car_tuning::CarTuning;
 use crate::player_id::PlayerId;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Game room config
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GameRoomConfig {
     pub players: Vec<PlayerId>,
+    /// Watch the race without driving, don't count towards the player count
+    #[serde(default)]
+    pub spectators: Vec<PlayerId>,
+    /// Rounds are raced until someone won more than half of them
+    #[serde(default = "default_best_of")]
+    pub best_of: u16,
+    /// Every peer simulates with this tuning
+    #[serde(default)]
+    pub tuning: CarTuning,
+    /// `CarTuning::fingerprint` of the tuning the room was created with, peers refuse any other
+    #[serde(default)]
+    pub tuning_fingerprint: Option<String>,
+    /// Picked by every player when joining, players without a pick drive the default class
+    #[serde(default)]
+    pub car_classes: HashMap<PlayerId, CarClass>,
+}
+
+fn default_best_of() -> u16 {
+    1
 }
@@ -0,0 +1,30 @@
+import {FormControl, InputLabel, MenuItem, Select, SelectChangeEvent} from "@mui/material";
+import {carClasses} from "@/domain/lobby";
+
+export interface CarClassSelectProps {
+  value: string
+  onChange: (carClass: string) => void
+  disabled: boolean
+}
+
+export function CarClassSelect(props: CarClassSelectProps) {
+  const onChange = (e: SelectChangeEvent<string>) => props.onChange(e.target.value)
+
+  return (
+    <FormControl fullWidth>
+      <InputLabel id="car-class-select-label">Car</InputLabel>
+      <Select
+        labelId="car-class-select-label"
+        id="car-class-select"
+        value={props.value}
+        label="Car"
+        onChange={onChange}
+        disabled={props.disabled}
+      >
+        {carClasses.map(carClass =>
+          <MenuItem key={carClass} value={carClass}>{carClass[0].toUpperCase() + carClass.slice(1)}</MenuItem>
+        )}
+      </Select>
+    </FormControl>
+  )
+}
@@ -5,7 +5,7 @@ import {
   FormControl,
   FormGroup,
   InputLabel, MenuItem,
-  Select, SelectChangeEvent,
+  Select, SelectChangeEvent, TextField,
 } from "@mui/material";
 import {useState} from "react";
 import {pipe} from "fp-ts/function";
@@ -19,6 +19,7 @@ import Alert from "@mui/material/Alert";
 import {envConfig} from "@/services/config";
 import {useRouter} from "next/router";
 import {routerPushRoomConnect} from "@/services/game";
+import {CarClassSelect} from "@/components/carClassSelect";
 
 export interface CreateGameProps {
   user: UserT
@@ -34,6 +35,12 @@ export default function CreateGame(props: CreateGameProps) {
 
   const [playerCount, setPlayerCount] = useState(2)
 
+  const [bestOf, setBestOf] = useState(1)
+
+  const [carClass, setCarClass] = useState("hatchback")
+
+  const [tuningJson, setTuningJson] = useState("")
+
   const [isRoomReady, setIsRoomReady] = useState(false)
 
   const onPlayerChange = (e: SelectChangeEvent<number>) => {
@@ -41,6 +48,21 @@ export default function CreateGame(props: CreateGameProps) {
     setPlayerCount(Number(e.target.value))
   }
 
+  const onBestOfChange = (e: SelectChangeEvent<number>) => {
+    flushErrors(setErrors, O.none)
+    setBestOf(Number(e.target.value))
+  }
+
+  const onCarClassChange = (carClass: string) => {
+    flushErrors(setErrors, O.none)
+    setCarClass(carClass)
+  }
+
+  const onTuningChange = (e: any) => {
+    flushErrors(setErrors, O.none)
+    setTuningJson(e.target.value)
+  }
+
   const onRoomReady = (user: UserT, roomId: RoomIdT, roomConfig: RoomConfigT) => {
     setIsRoomReady(true)
     routerPushRoomConnect(router, setErrors, user, roomId, roomConfig)
@@ -59,7 +81,7 @@ export default function CreateGame(props: CreateGameProps) {
   }
 
   const onSubmit = () => {
-    postCreateRoom(envConfig.httpServer, props.user.id, props.user.ticket, playerCount).then((result) =>
+    postCreateRoom(envConfig.httpServer, props.user.id, props.user.ticket, playerCount, bestOf, carClass, tuningJson).then((result) =>
       pipe(
         result,
         E.match(
@@ -99,6 +121,38 @@ export default function CreateGame(props: CreateGameProps) {
             </Select>
           </FormControl>
         </FormGroup>
+        <FormGroup sx={{ marginBottom: 2 }}>
+          <FormControl fullWidth>
+            <InputLabel id="best-of-select-label">Rounds</InputLabel>
+            <Select
+              labelId="best-of-select-label"
+              id="best-of-select"
+              value={bestOf}
+              label="Rounds"
+              onChange={onBestOfChange}
+              disabled={isSome(roomId)}
+            >
+              {[1, 3, 5, 7].map(i =>
+                <MenuItem key={i} value={i}>{i == 1 ? "Single race" : `Best of ${i}`}</MenuItem>
+              )}
+            </Select>
+          </FormControl>
+        </FormGroup>
+        <FormGroup sx={{ marginBottom: 2 }}>
+          <CarClassSelect value={carClass} onChange={onCarClassChange} disabled={isSome(roomId)} />
+        </FormGroup>
+        <FormGroup sx={{ marginBottom: 2 }}>
+          <TextField
+            id="tuning-json"
+            label="Car tuning JSON (optional)"
+            placeholder='{"acceleration_force": 160, "max_steering_degrees": 40}'
+            multiline
+            minRows={2}
+            value={tuningJson}
+            onChange={onTuningChange}
+            disabled={isSome(roomId)}
+          />
+        </FormGroup>
       </FormControl>
       {isSome(formMetaErrors) &&
           <Alert severity="error">{getOrElse<string>(() => "")(formMetaErrors)}</Alert>
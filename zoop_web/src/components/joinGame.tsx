@@ -31,12 +31,13 @@ import {Box} from "@mui/system";
 import {TabPanel} from "@/components/tabPanel";
 import Alert from "@mui/material/Alert";
 import {addFormKeyError, flushErrors, keyErrorMessage, newFormErrors} from "@/domain/formError";
-import {getRoomReady, postCreateRoom, postJoinRoom} from "@/services/lobby";
+import {getRoomReady, postCreateRoom, postJoinRoom, postSpectateRoom} from "@/services/lobby";
 import {AppError} from "@/domain/appError";
 import {RoomConfigT, RoomIdT} from "@/domain/lobby";
 import {envConfig} from "@/services/config";
 import {set} from "fp-ts";
 import {routerPushRoomConnect} from "@/services/game";
+import {CarClassSelect} from "@/components/carClassSelect";
 
 export interface JoinGameProps {
   user: UserT
@@ -52,6 +53,8 @@ export default function JoinGame(props: JoinGameProps) {
     setRoomId(e.target.value)
   }
 
+  const [carClass, setCarClass] = useState("hatchback")
+
   const [isRoomReady, setIsRoomReady] = useState(false)
   const [isWaitingRoomReady, setIsWaitingRoomReady] = useState(false)
 
@@ -79,7 +82,20 @@ export default function JoinGame(props: JoinGameProps) {
   }
 
   const onSubmit = (user: UserT, roomId: RoomIdT) => {
-    postJoinRoom(envConfig.httpServer, roomId, props.user.id, props.user.ticket).then((result) =>
+    postJoinRoom(envConfig.httpServer, roomId, props.user.id, props.user.ticket, carClass).then((result) =>
+      pipe(
+        result,
+        O.match(
+          () => onWaitRoomReady(user, roomId),
+          (error) => addFormKeyError(setErrors, O.none, error)
+        )
+      )
+    )
+  }
+
+  // Spectators wait for the same room config, but don't drive a car
+  const onSpectate = (user: UserT, roomId: RoomIdT) => {
+    postSpectateRoom(envConfig.httpServer, roomId, props.user.id, props.user.ticket).then((result) =>
       pipe(
         result,
         O.match(
@@ -103,6 +119,7 @@ export default function JoinGame(props: JoinGameProps) {
         helperText={getOrElse<string>(() => "")(roomIdErrors)}
         value={roomId} onChange={onRoomIdChange} disabled={isWaitingRoomReady || isRoomReady}
         required={true} id="roomId" label="Room code" variant="outlined" />
+      <CarClassSelect value={carClass} onChange={setCarClass} disabled={isWaitingRoomReady || isRoomReady} />
       {isSome(formMetaErrors) &&
           <Alert severity="error">{getOrElse<string>(() => "")(formMetaErrors)}</Alert>
       }
@@ -110,7 +127,13 @@ export default function JoinGame(props: JoinGameProps) {
         ? <Button variant="contained" onClick={onCancel}>Launching game... (Cancel)</Button>
         : isWaitingRoomReady
           ? <Button variant="contained" onClick={onCancel}>Waiting for players... (Cancel)</Button>
-          : <Button variant="contained" onClick={() => onSubmit(props.user, roomId)}>Join</Button>
+          : <>
+              <Button variant="contained" onClick={() => onSubmit(props.user, roomId)}>Join</Button>
+              <Button variant="outlined" onClick={() => onSpectate(props.user, roomId)}>Spectate before start</Button>
+              <Typography variant="caption">
+                The host only adds spectators when the race starts, a race which already started can't be watched
+              </Typography>
+            </>
       }
     </Stack>
   )
@@ -4,6 +4,14 @@ export const RoomId = t.string
 export type RoomIdT = t.TypeOf<typeof RoomId>
 
 export const RoomConfig = t.type({
-  players: t.array(t.string)
+  players: t.array(t.string),
+  spectators: t.union([t.array(t.string), t.undefined]),
+  best_of: t.union([t.number, t.undefined]),
+  // Passed on to the engine as is
+  tuning: t.unknown,
+  tuning_fingerprint: t.union([t.string, t.undefined]),
+  car_classes: t.union([t.record(t.string, t.string), t.undefined])
 })
-export type RoomConfigT = t.TypeOf<typeof RoomConfig>
\ No newline at end of file
+export type RoomConfigT = t.TypeOf<typeof RoomConfig>
+// Same names as the engine's car catalogue
+export const carClasses = ["hatchback", "truck", "kart"]
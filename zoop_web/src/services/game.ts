@@ -68,6 +68,9 @@ export function connectRoom(
   }
 }
 
+// Key bindings JSON, same format as the native `key_bindings.json` settings file
+export const KEY_BINDINGS_STORAGE_KEY = "zoop_key_bindings"
+
 export function connectRoomWasm(roomDetails: RoomConnect, canvasSelector: string) {
   fetch("/zoop_engine_bg.wasm")
     .then((response) => response.arrayBuffer())
@@ -86,6 +89,7 @@ export function connectRoomWasm(roomDetails: RoomConnect, canvasSelector: string
           uuidStringify(roomDetails.roomId.value),
           JSON.stringify(roomDetails.roomConfig),
           canvasSelector,
+          localStorage.getItem(KEY_BINDINGS_STORAGE_KEY),
         )
       } catch (error: any) {
         if (!error.message.startsWith("Using exceptions for control flow,")) {
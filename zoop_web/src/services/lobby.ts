@@ -6,15 +6,24 @@ import {RoomConfig, RoomConfigT, RoomId, RoomIdT} from "@/domain/lobby";
 import {Option} from "fp-ts/Option";
 import {parsedServerError, parsedServerJson} from "@/services/fetch";
 
-export function postCreateRoom(zoopHttpServer: string, playerId: string, ticket: string, playerCount: number): Promise<Either<AppError, RoomIdT>> {
-  return fetch(`${zoopHttpServer}/api/game/new/by/${playerId}/ticket/${encodeURIComponent(ticket)}/player_count/${playerCount}`, { method: "POST"})
+export function postCreateRoom(zoopHttpServer: string, playerId: string, ticket: string, playerCount: number, bestOf: number, carClass: string, tuningJson: string): Promise<Either<AppError, RoomIdT>> {
+  // Without a car tuning body the server uses the default handling
+  const tuning = tuningJson.trim() == "" ? {} : { headers: { "Content-Type": "application/json" }, body: tuningJson }
+  return fetch(`${zoopHttpServer}/api/game/new/by/${playerId}/ticket/${encodeURIComponent(ticket)}/player_count/${playerCount}?best_of=${bestOf}&car_class=${carClass}`, { method: "POST", ...tuning })
     .then((response) => response.json())
     .then((json) =>  parsedServerJson(json, "RoomIdT", RoomId.decode))
     .catch((reason) => E.left(new FetchError(reason)))
 }
 
-export function postJoinRoom(zoopHttpServer: string, roomId: string, playerId: string, ticket: string): Promise<Option<AppError>> {
-  return fetch(`${zoopHttpServer}/api/game/join/${roomId}/by/${playerId}/ticket/${encodeURIComponent(ticket)}`, { method: "POST"})
+export function postJoinRoom(zoopHttpServer: string, roomId: string, playerId: string, ticket: string, carClass: string): Promise<Option<AppError>> {
+  return fetch(`${zoopHttpServer}/api/game/join/${roomId}/by/${playerId}/ticket/${encodeURIComponent(ticket)}?car_class=${carClass}`, { method: "POST"})
+    .then((response) => response.json())
+    .then(parsedServerError)
+    .catch((reason) => O.some(new FetchError(reason)))
+}
+
+export function postSpectateRoom(zoopHttpServer: string, roomId: string, playerId: string, ticket: string): Promise<Option<AppError>> {
+  return fetch(`${zoopHttpServer}/api/game/spectate/${roomId}/by/${playerId}/ticket/${encodeURIComponent(ticket)}`, { method: "POST"})
     .then((response) => response.json())
     .then(parsedServerError)
     .catch((reason) => O.some(new FetchError(reason)))
@@ -7,6 +7,7 @@ use clap::Parser;
 use uuid::Uuid;
 use zoop_cli::*;
 use zoop_shared::player_id::PlayerId;
+use zoop_shared::race_result::RaceResult;
 use zoop_shared::room_id::RoomId;
 
 #[tokio::main]
@@ -34,7 +35,7 @@ async fn connect_game(
     user_ticket: String,
     room_uuid: String,
     room_config_json: String,
-) -> Result<(), String> {
+) -> Result<Option<RaceResult>, String> {
     let user_id = PlayerId(Uuid::parse_str(&user_uuid).unwrap());
     let room_id = RoomId(Uuid::parse_str(&room_uuid).unwrap());
     let room_config = serde_json::from_str(&room_config_json).unwrap();